@@ -0,0 +1,20 @@
+use crate::common::{util::random_secp256r1_signing_key, HostCostMeasurement};
+use rand::rngs::StdRng;
+use soroban_env_host::{cost_runner::ComputeEcdsaSecp256r1PubKeyRun, Host};
+
+// This measures the costs to turn one byte buffer (an uncompressed SEC1
+// point) into a secp256r1 pubkey, which should be constant time. The input
+// value is ignored.
+pub(crate) struct ComputeEcdsaSecp256r1PubKeyMeasure;
+
+impl HostCostMeasurement for ComputeEcdsaSecp256r1PubKeyMeasure {
+    type Runner = ComputeEcdsaSecp256r1PubKeyRun;
+
+    fn new_random_case(_host: &Host, rng: &mut StdRng, _input: u64) -> Vec<u8> {
+        let key = random_secp256r1_signing_key(rng);
+        key.verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec()
+    }
+}
@@ -1,5 +1,6 @@
 mod bytes_ops;
 mod charge_budget;
+mod compute_ecdsa_secp256r1_pubkey;
 mod compute_ed25519_pubkey;
 mod compute_sha256_hash;
 mod ed25519_scalar_mul;
@@ -15,6 +16,7 @@ mod scvec_to_host_vec;
 mod val_deser;
 mod val_ser;
 mod val_xdr_conv;
+mod verify_ecdsa_secp256r1_sig;
 mod verify_ed25519_sig;
 mod visit_object;
 mod vm_ops;
@@ -22,6 +24,7 @@ mod wasm_insn_exec;
 
 pub(crate) use bytes_ops::*;
 pub(crate) use charge_budget::*;
+pub(crate) use compute_ecdsa_secp256r1_pubkey::*;
 pub(crate) use compute_ed25519_pubkey::*;
 pub(crate) use compute_sha256_hash::*;
 pub(crate) use ed25519_scalar_mul::*;
@@ -37,6 +40,7 @@ pub(crate) use scvec_to_host_vec::*;
 pub(crate) use val_deser::*;
 pub(crate) use val_ser::*;
 pub(crate) use val_xdr_conv::*;
+pub(crate) use verify_ecdsa_secp256r1_sig::*;
 pub(crate) use verify_ed25519_sig::*;
 pub(crate) use visit_object::*;
 pub(crate) use vm_ops::*;
@@ -0,0 +1,30 @@
+use crate::common::{util::random_secp256r1_signing_key, HostCostMeasurement};
+use p256::ecdsa::{signature::Signer, Signature};
+use rand::rngs::StdRng;
+use soroban_env_host::{
+    cost_runner::{VerifyEcdsaSecp256r1SigRun, VerifyEcdsaSecp256r1SigSample},
+    Host,
+};
+
+pub(crate) struct VerifyEcdsaSecp256r1SigMeasure;
+
+// This measures the cost of verifying a secp256r1 signature of varying-length
+// messages. The input value is the length of the signed message. It should
+// cost constant CPU for the verification itself plus linear CPU for hashing
+// and zero heap memory.
+impl HostCostMeasurement for VerifyEcdsaSecp256r1SigMeasure {
+    type Runner = VerifyEcdsaSecp256r1SigRun;
+
+    fn new_random_case(
+        _host: &Host,
+        rng: &mut StdRng,
+        input: u64,
+    ) -> VerifyEcdsaSecp256r1SigSample {
+        let size = input * 10000;
+        let signing_key = random_secp256r1_signing_key(rng);
+        let key = signing_key.verifying_key();
+        let msg: Vec<u8> = (0..size).map(|x| x as u8).collect();
+        let sig: Signature = signing_key.sign(msg.as_slice());
+        VerifyEcdsaSecp256r1SigSample { key, msg, sig }
+    }
+}
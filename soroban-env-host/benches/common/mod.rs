@@ -59,6 +59,8 @@ pub(crate) fn for_each_host_cost_measurement<B: Benchmark>() -> std::io::Result<
     call_bench::<B, ScMapToHostMapMeasure>(&mut costs)?;
     call_bench::<B, ScVecToHostVecMeasure>(&mut costs)?;
     call_bench::<B, VerifyEd25519SigMeasure>(&mut costs)?;
+    call_bench::<B, ComputeEcdsaSecp256r1PubKeyMeasure>(&mut costs)?;
+    call_bench::<B, VerifyEcdsaSecp256r1SigMeasure>(&mut costs)?;
     call_bench::<B, VmInstantiationMeasure>(&mut costs)?;
     call_bench::<B, VmMemReadMeasure>(&mut costs)?;
     call_bench::<B, VmMemWriteMeasure>(&mut costs)?;
@@ -1,5 +1,20 @@
+use rand::{rngs::StdRng, Rng};
 use soroban_env_host::RawVal;
 
 pub(crate) fn to_rawval_u32<I: Iterator<Item = u32>>(vals: I) -> impl Iterator<Item = RawVal> {
     vals.map(move |v| RawVal::from_u32(v))
 }
+
+// Generates a random secp256r1 signing key. The key is derived from random
+// bytes rather than generated directly, as `p256` requires a newer `rand_core`
+// than the one used by the benchmarks.
+pub(crate) fn random_secp256r1_signing_key(rng: &mut StdRng) -> p256::ecdsa::SigningKey {
+    loop {
+        let bytes: [u8; 32] = rng.gen();
+        // Fails only if the bytes are not a valid scalar, which is extremely
+        // unlikely.
+        if let Ok(key) = p256::ecdsa::SigningKey::from_bytes(&bytes) {
+            return key;
+        }
+    }
+}
@@ -2,21 +2,67 @@ use std::collections::HashMap;
 
 use soroban_env_common::xdr::{
     ContractAuth, ContractDataEntry, HashIdPreimage, HashIdPreimageContractAuth, LedgerEntry,
-    LedgerEntryData, LedgerEntryExt, ScAddress, ScHostAuthErrorCode, ScObject, ScVal,
+    LedgerEntryData, LedgerEntryExt, ScAddress, ScHostAuthErrorCode, ScHostFnErrorCode, ScObject,
+    ScVal, ThresholdIndexes,
 };
-use soroban_env_common::{RawVal, Symbol};
+use soroban_env_common::{Object, RawVal, Symbol, TryFromVal};
 
 use crate::budget::Budget;
+use crate::events::DebugEvent;
 use crate::host::metered_clone::MeteredClone;
 use crate::host::Frame;
 use crate::native_contract::account_contract::{
-    check_account_authentication, check_account_contract_auth,
+    check_account_authentication, check_account_contract_auth, simulate_account_authentication,
+    simulate_account_contract_auth, InvokerContractAuthEntry,
 };
+use crate::storage::{AccessType, FootprintMode};
 use crate::{Host, HostError};
 
 use super::xdr;
 use super::xdr::{Hash, ScUnknownErrorCode, ScVec};
 
+pub mod export;
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+
+use export::AuthorizationTree;
+
+// Nonces with the highest bit set are 'unordered': instead of having to match
+// the next value of the sequential per-address nonce counter, they only need
+// to be unique among the unordered nonces consumed by the address for the
+// same contract. This allows independently signed authorizations to be
+// submitted in any order.
+// The signatures of unordered nonces expire: the next 32 bits of the nonce
+// (see `UNORDERED_NONCE_EXPIRATION_SHIFT`) hold the last ledger at which the
+// signature is still valid. Since the nonce is a part of the signature
+// payload, the expiration ledger can't be modified without invalidating the
+// signature. The remaining low bits tell apart the unordered nonces expiring
+// at the same ledger.
+pub const UNORDERED_NONCE_FLAG: u64 = 1 << 63;
+pub const UNORDERED_NONCE_EXPIRATION_SHIFT: u32 = 31;
+
+pub fn is_unordered_nonce(nonce: u64) -> bool {
+    nonce & UNORDERED_NONCE_FLAG != 0
+}
+
+// Builds the unordered nonce whose signature expires after the given ledger.
+// Only the low 31 bits of `salt` are used.
+pub fn unordered_nonce(signature_expiration_ledger: u32, salt: u32) -> u64 {
+    UNORDERED_NONCE_FLAG
+        | (signature_expiration_ledger as u64) << UNORDERED_NONCE_EXPIRATION_SHIFT
+        | (salt as u64) & ((1 << UNORDERED_NONCE_EXPIRATION_SHIFT) - 1)
+}
+
+// Returns the last ledger at which the signature of `nonce` is still valid,
+// or `None` if the signature doesn't expire (i.e. the nonce is sequential).
+pub fn nonce_signature_expiration_ledger(nonce: u64) -> Option<u32> {
+    if is_unordered_nonce(nonce) {
+        Some(((nonce & !UNORDERED_NONCE_FLAG) >> UNORDERED_NONCE_EXPIRATION_SHIFT) as u32)
+    } else {
+        None
+    }
+}
+
 // Authorization manager encapsulates host-based authentication & authorization
 // framework.
 // This supports enforcing authentication & authorization of the contract
@@ -35,9 +81,23 @@ pub(crate) struct AuthorizationManager {
     // Current call stack consisting only of the contract invocations (i.e. not
     // the host functions).
     call_stack: Vec<ContractInvocation>,
+    // Trackers of the invocations authorized by the contracts on their own
+    // behalf (via `authorize_as_curr_contract`). These are only valid while
+    // the authorizing contract is on the call stack.
+    invoker_contract_trackers: Vec<InvokerContractAuthorizationTracker>,
     budget: Budget,
 }
 
+// Tracks the invocation tree authorized by a contract for the calls made
+// (directly or indirectly) by its current invocation.
+#[derive(Clone)]
+struct InvokerContractAuthorizationTracker {
+    tracker: AuthorizationTracker,
+    // Length of the call stack at the moment of authorization, i.e. the depth
+    // of the authorizing contract invocation.
+    call_stack_depth: usize,
+}
+
 // The authorization payload recorded for an address in the recording
 // authorization mode.
 #[derive(Eq, PartialEq, Debug)]
@@ -55,6 +115,7 @@ pub struct AuthorizationManagerSnapshot {
     // rollback everything. If this is an issue, then the AuthorizationTracker should
     // probably be separated into 'mutable' and 'immutable' parts.
     trackers: Vec<AuthorizationTracker>,
+    invoker_contract_trackers: Vec<InvokerContractAuthorizationTracker>,
     tracker_by_address_handle: Option<HashMap<u32, usize>>,
 }
 
@@ -73,6 +134,40 @@ struct RecordingAuthInfo {
     // value, but are specified as two different objects (e.g. as two different
     // contract function inputs).
     tracker_by_address_handle: HashMap<u32, usize>,
+    // When set, unordered nonces are recorded instead of the sequential ones.
+    unordered_nonces: Option<UnorderedNonceConfig>,
+}
+
+// Configuration of the unordered nonce generation in the recording mode.
+#[derive(Clone)]
+struct UnorderedNonceConfig {
+    // Caller-provided randomness the nonces are derived from, so that the
+    // recording itself stays deterministic.
+    seed: [u8; 32],
+    // Number of nonces derived so far. This is preserved when the recorded
+    // authorizations are reset, so that the subsequent invocations don't
+    // record the same nonces again.
+    nonce_count: u64,
+    // Number of ledgers after the current one at which the recorded
+    // signatures expire.
+    signature_lifetime_ledgers: u32,
+}
+
+impl UnorderedNonceConfig {
+    // Derives the next unordered nonce.
+    fn next_nonce(&mut self, host: &Host) -> Result<u64, HostError> {
+        let mut buf = self.seed.to_vec();
+        buf.extend_from_slice(&self.nonce_count.to_be_bytes());
+        self.nonce_count += 1;
+        let hash = host.metered_sha256(&buf)?;
+        let mut salt_bytes = [0u8; 4];
+        salt_bytes.copy_from_slice(&hash[0..4]);
+        let ledger_seq = host.with_ledger_info(|li| Ok(li.sequence_number))?;
+        Ok(unordered_nonce(
+            ledger_seq.saturating_add(self.signature_lifetime_ledgers),
+            u32::from_be_bytes(salt_bytes),
+        ))
+    }
 }
 
 // Stores all the authorizations that are authorized by an address.
@@ -112,6 +207,15 @@ struct AuthorizationTracker {
     // The value of nonce authorized by the address. Must match the stored
     // nonce value.
     nonce: Option<u64>,
+    // The last ledger sequence number at which the signature is still valid.
+    // This is encoded in the (unordered) nonce, so that it is also a part of
+    // the signature payload.
+    signature_expiration_ledger: Option<u32>,
+    // Indicates whether signature verification should only be emulated (with
+    // all of its costs still charged) instead of actually performed. This is
+    // used for the enforcing mode preflight, when the signatures aren't
+    // available yet.
+    simulate_signatures: bool,
 }
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -171,6 +275,25 @@ impl AuthorizedInvocation {
         })
     }
 
+    // Builds the invocation tree authorized by a contract on its own behalf.
+    fn from_invoker_contract_entry(
+        host: &Host,
+        entry: InvokerContractAuthEntry,
+    ) -> Result<Self, HostError> {
+        let mut sub_invocations = vec![];
+        for i in 0..entry.sub_invocs.len()? {
+            let sub_entry: InvokerContractAuthEntry = entry.sub_invocs.get(i)?;
+            sub_invocations.push(Self::from_invoker_contract_entry(host, sub_entry)?);
+        }
+        Ok(Self {
+            contract_id: Hash(entry.contract.to_array()?),
+            function_name: entry.fn_name,
+            args: host.call_args_to_scvec(entry.args.into())?,
+            sub_invocations,
+            is_exhausted: false,
+        })
+    }
+
     fn new_recording(contract_id: &Hash, function_name: Symbol, args: ScVec) -> Self {
         Self {
             contract_id: contract_id.clone(),
@@ -225,6 +348,21 @@ impl AuthorizedInvocation {
         }
         self
     }
+
+    // Immutable counterpart of `last_authorized_invocation_mut`.
+    fn last_authorized_invocation(
+        &self,
+        invocation_id_in_call_stack: &Vec<Option<usize>>,
+        call_stack_id: usize,
+    ) -> &AuthorizedInvocation {
+        for i in call_stack_id..invocation_id_in_call_stack.len() {
+            if let Some(id) = invocation_id_in_call_stack[i] {
+                return self.sub_invocations[id]
+                    .last_authorized_invocation(invocation_id_in_call_stack, i + 1);
+            }
+        }
+        self
+    }
 }
 
 impl Default for AuthorizationManager {
@@ -252,9 +390,26 @@ impl AuthorizationManager {
             call_stack: vec![],
             budget: host.budget_cloned(),
             trackers,
+            invoker_contract_trackers: vec![],
         })
     }
 
+    // Creates a new enforcing `AuthorizationManager` from the given
+    // authorization entries that doesn't verify the signatures, but still
+    // charges the budget as if it did. This is meant for the enforcing mode
+    // preflight, i.e. when the authorization entries are known, but not
+    // signed yet.
+    pub(crate) fn new_enforcing_for_simulation(
+        host: &Host,
+        auth_entries: Vec<ContractAuth>,
+    ) -> Result<Self, HostError> {
+        let mut auth_manager = Self::new_enforcing(host, auth_entries)?;
+        for tracker in &mut auth_manager.trackers {
+            tracker.simulate_signatures = true;
+        }
+        Ok(auth_manager)
+    }
+
     // Creates a new enforcing `AuthorizationManager` that doesn't allow any
     // authorizations.
     // This is useful as a safe default mode.
@@ -264,6 +419,7 @@ impl AuthorizationManager {
             call_stack: vec![],
             budget,
             trackers: vec![],
+            invoker_contract_trackers: vec![],
         }
     }
 
@@ -271,13 +427,37 @@ impl AuthorizationManager {
     // All the authorization requirements will be recorded and can then be
     // retrieved using `get_recorded_auth_payloads`.
     pub(crate) fn new_recording(budget: Budget) -> Self {
+        Self::new_recording_impl(budget, None)
+    }
+
+    // Creates a new recording `AuthorizationManager` that records unordered
+    // nonces derived from `seed`, with signatures expiring
+    // `signature_lifetime_ledgers` after the current ledger.
+    pub(crate) fn new_recording_with_unordered_nonces(
+        budget: Budget,
+        seed: [u8; 32],
+        signature_lifetime_ledgers: u32,
+    ) -> Self {
+        Self::new_recording_impl(
+            budget,
+            Some(UnorderedNonceConfig {
+                seed,
+                nonce_count: 0,
+                signature_lifetime_ledgers,
+            }),
+        )
+    }
+
+    fn new_recording_impl(budget: Budget, unordered_nonces: Option<UnorderedNonceConfig>) -> Self {
         Self {
             mode: AuthorizationMode::Recording(RecordingAuthInfo {
                 tracker_by_address_handle: Default::default(),
+                unordered_nonces,
             }),
             call_stack: vec![],
             budget,
             trackers: vec![],
+            invoker_contract_trackers: vec![],
         }
     }
 
@@ -292,17 +472,33 @@ impl AuthorizationManager {
         address_obj_handle: u32,
         address: ScAddress,
         args: ScVec,
+        threshold: ThresholdIndexes,
     ) -> Result<(), HostError> {
         if let ScAddress::Contract(contract_addr) = &address {
-            // For now we give a blanket approval of the invoker contract to any
-            // calls it made, but never to the deeper calls. It's possible
-            // to eventually add a capability to pre-authorize arbitrary call
-            // stacks on behalf of the contract.
+            // We give a blanket approval of the invoker contract to any
+            // calls it made. Deeper calls have to be explicitly authorized by
+            // the contract via `authorize_as_curr_contract`.
             if let Ok(invoker_contract) = host.get_invoking_contract_internal() {
                 if &invoker_contract == contract_addr {
                     return Ok(());
                 }
             }
+            if let Some(curr_invocation) = self.call_stack.last() {
+                for invoker_tracker in &mut self.invoker_contract_trackers {
+                    let tracker = &mut invoker_tracker.tracker;
+                    if tracker.address.as_ref() == Some(&address)
+                        && tracker.maybe_authorize_invocation(
+                            host,
+                            &curr_invocation.contract_id,
+                            curr_invocation.function_name,
+                            &args,
+                            threshold,
+                        )?
+                    {
+                        return Ok(());
+                    }
+                }
+            }
         }
 
         if let Some(curr_invocation) = self.call_stack.last() {
@@ -330,6 +526,7 @@ impl AuthorizationManager {
                                 &curr_invocation.contract_id,
                                 curr_invocation.function_name,
                                 &args,
+                                threshold,
                             ) {
                                 // If tracker doesn't have a matching invocation,
                                 // just skip it (there could still be another
@@ -345,8 +542,24 @@ impl AuthorizationManager {
                         }
                     }
                     // No matching tracker found, hence the invocation isn't
-                    // authorized.
-                    Err(ScHostAuthErrorCode::NotAuthorized.into())
+                    // authorized. Explain why none of the trackers matched
+                    // before failing. Diagnostics are charged to the debug
+                    // budget and can't affect the outcome, so their failure
+                    // is ignored.
+                    let trackers = &self.trackers;
+                    let _ = host.with_debug_budget(|| {
+                        record_unauthorized_diagnostics(
+                            host,
+                            trackers,
+                            &address,
+                            curr_invocation,
+                            &args,
+                        )
+                    });
+                    Err(host.err_status_msg(
+                        ScHostAuthErrorCode::NotAuthorized,
+                        "invocation is not authorized, see the preceding debug events for details",
+                    ))
                 }
                 AuthorizationMode::Recording(recording_info) => {
                     if let Some(tracker_id) = recording_info
@@ -374,6 +587,10 @@ impl AuthorizationManager {
                     }
                     // If a tracker for the new tree doesn't exist yet, create
                     // it and initialize with the current invocation.
+                    let unordered_nonce = match &mut recording_info.unordered_nonces {
+                        Some(config) => Some(config.next_nonce(host)?),
+                        None => None,
+                    };
                     self.trackers.push(AuthorizationTracker::new_recording(
                         host,
                         address,
@@ -381,6 +598,7 @@ impl AuthorizationManager {
                         curr_invocation.function_name,
                         args,
                         self.call_stack.len(),
+                        unordered_nonce,
                     )?);
                     recording_info
                         .tracker_by_address_handle
@@ -394,6 +612,33 @@ impl AuthorizationManager {
         }
     }
 
+    // Authorizes the invocation trees in `auth_entries` on behalf of the
+    // current contract. The authorizations apply to the calls made below the
+    // current invocation and are dropped when it returns.
+    pub(crate) fn add_invoker_contract_auth(
+        &mut self,
+        host: &Host,
+        contract_id: Hash,
+        invocations: Vec<AuthorizedInvocation>,
+    ) -> Result<(), HostError> {
+        let call_stack_depth = self.call_stack.len();
+        if call_stack_depth == 0 {
+            return Err(host.err_general("unexpected empty stack in invoker contract auth"));
+        }
+        for invocation in invocations {
+            self.invoker_contract_trackers
+                .push(InvokerContractAuthorizationTracker {
+                    tracker: AuthorizationTracker::new_invoker_contract(
+                        contract_id.metered_clone(&self.budget)?,
+                        invocation,
+                        call_stack_depth,
+                    ),
+                    call_stack_depth,
+                });
+        }
+        Ok(())
+    }
+
     // Returns a snapshot of `AuthorizationManager` to use for rollback.
     pub(crate) fn snapshot(&self) -> AuthorizationManagerSnapshot {
         let tracker_by_address_handle = match &self.mode {
@@ -404,6 +649,7 @@ impl AuthorizationManager {
         };
         AuthorizationManagerSnapshot {
             trackers: self.trackers.clone(),
+            invoker_contract_trackers: self.invoker_contract_trackers.clone(),
             tracker_by_address_handle,
         }
     }
@@ -411,6 +657,7 @@ impl AuthorizationManager {
     // Rolls back this `AuthorizationManager` to the snapshot state.
     pub(crate) fn rollback(&mut self, snapshot: AuthorizationManagerSnapshot) {
         self.trackers = snapshot.trackers;
+        self.invoker_contract_trackers = snapshot.invoker_contract_trackers;
         if let Some(tracker_by_address_handle) = snapshot.tracker_by_address_handle {
             match &mut self.mode {
                 AuthorizationMode::Recording(recording_info) => {
@@ -444,6 +691,9 @@ impl AuthorizationManager {
         for tracker in &mut self.trackers {
             tracker.push_frame();
         }
+        for invoker_tracker in &mut self.invoker_contract_trackers {
+            invoker_tracker.tracker.push_frame();
+        }
         Ok(())
     }
 
@@ -462,6 +712,14 @@ impl AuthorizationManager {
         for tracker in &mut self.trackers {
             tracker.pop_frame();
         }
+        // Drop the authorizations made by the contract invocation that has
+        // just returned.
+        let call_stack_len = self.call_stack.len();
+        self.invoker_contract_trackers
+            .retain(|t| t.call_stack_depth <= call_stack_len);
+        for invoker_tracker in &mut self.invoker_contract_trackers {
+            invoker_tracker.tracker.pop_frame();
+        }
     }
 
     // Returns the recorded per-address authorization payloads that would cover the
@@ -478,6 +736,15 @@ impl AuthorizationManager {
         }
     }
 
+    // Returns the authorization trees of all the addresses tracked in either
+    // mode.
+    pub(crate) fn get_authorization_trees(&self) -> Vec<AuthorizationTree> {
+        self.trackers
+            .iter()
+            .map(AuthorizationTree::from_tracker)
+            .collect()
+    }
+
     // For recording mode, emulates authentication that would normally happen in
     // the enforcing mode.
     // This helps to build a more realistic footprint and produce more correct
@@ -503,8 +770,11 @@ impl AuthorizationManager {
             AuthorizationMode::Enforcing => {
                 AuthorizationManager::new_enforcing_without_authorizations(self.budget.clone())
             }
-            AuthorizationMode::Recording(_) => {
-                AuthorizationManager::new_recording(self.budget.clone())
+            AuthorizationMode::Recording(ref recording_info) => {
+                AuthorizationManager::new_recording_impl(
+                    self.budget.clone(),
+                    recording_info.unordered_nonces.clone(),
+                )
             }
         }
     }
@@ -535,6 +805,89 @@ impl AuthorizationManager {
     }
 }
 
+// Reason why an `AuthorizationTracker` couldn't authorize an invocation,
+// reported in the debug events when authorization fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AuthMismatchReason {
+    AddressMismatch,
+    Invalidated,
+    FrameAlreadyAuthorized,
+    NoMatchingInvocation,
+    InvocationExhausted,
+    ArgsMismatch,
+}
+
+impl AuthMismatchReason {
+    fn description(&self) -> &'static str {
+        match self {
+            AuthMismatchReason::AddressMismatch => "authorized address doesn't match",
+            AuthMismatchReason::Invalidated => {
+                "authorization entry has been invalidated by a previous failure"
+            }
+            AuthMismatchReason::FrameAlreadyAuthorized => {
+                "current call has already been authorized by this entry"
+            }
+            AuthMismatchReason::NoMatchingInvocation => {
+                "no authorized invocation of this contract function at the current call depth"
+            }
+            AuthMismatchReason::InvocationExhausted => "matching invocation has already been used",
+            AuthMismatchReason::ArgsMismatch => "arguments don't match the authorized ones",
+        }
+    }
+}
+
+// Records the debug events explaining why none of the `trackers` has
+// authorized `invocation` with `args` on behalf of `address`.
+fn record_unauthorized_diagnostics(
+    host: &Host,
+    trackers: &[AuthorizationTracker],
+    address: &ScAddress,
+    invocation: &ContractInvocation,
+    args: &ScVec,
+) -> Result<(), HostError> {
+    host.record_debug_event(
+        DebugEvent::new()
+            .msg("auth failed: address {} has not authorized call of function {} of contract {} with args {}")
+            .arg(RawVal::from(host.add_host_object(address.metered_clone(host.budget_ref())?)?))
+            .arg(RawVal::from(invocation.function_name))
+            .arg(RawVal::from(
+                host.add_host_object(invocation.contract_id.0.to_vec())?,
+            ))
+            .arg(host.to_host_val(&ScVal::Object(Some(ScObject::Vec(
+                args.metered_clone(host.budget_ref())?,
+            ))))?),
+    )?;
+    for (i, tracker) in trackers.iter().enumerate() {
+        let (reason, authorized_args) = tracker.diagnose_mismatch(
+            address,
+            &invocation.contract_id,
+            invocation.function_name,
+            args,
+        );
+        if reason == AuthMismatchReason::AddressMismatch {
+            continue;
+        }
+        let event = match authorized_args {
+            Some(authorized_args) => DebugEvent::new()
+                .msg("auth entry {}: {}, authorized args: {}")
+                .arg(host.usize_to_rawval_u32(i)?)
+                .arg(reason.description())
+                .arg(host.to_host_val(&ScVal::Object(Some(ScObject::Vec(authorized_args))))?),
+            None => DebugEvent::new()
+                .msg("auth entry {}: {}")
+                .arg(host.usize_to_rawval_u32(i)?)
+                .arg(reason.description()),
+        };
+        host.record_debug_event(event)?;
+    }
+    if trackers.iter().all(|t| t.address.as_ref() != Some(address)) {
+        host.record_debug_event(
+            DebugEvent::new().msg("auth failed: no authorization entries for the address"),
+        )?;
+    }
+    Ok(())
+}
+
 impl AuthorizationTracker {
     fn from_authorization_entry(host: &Host, auth_entry: ContractAuth) -> Result<Self, HostError> {
         let is_invoker = auth_entry.address_with_nonce.is_none();
@@ -556,6 +909,8 @@ impl AuthorizationTracker {
             nonce,
             invocation_id_in_call_stack: vec![],
             is_valid: true,
+            signature_expiration_ledger: nonce.and_then(nonce_signature_expiration_ledger),
+            simulate_signatures: false,
         })
     }
 
@@ -566,6 +921,7 @@ impl AuthorizationTracker {
         function_name: Symbol,
         args: ScVec,
         current_stack_len: usize,
+        unordered_nonce: Option<u64>,
     ) -> Result<Self, HostError> {
         if current_stack_len == 0 {
             // This would be a bug.
@@ -579,10 +935,15 @@ impl AuthorizationTracker {
         } else {
             false
         };
-        let nonce = if !is_invoker {
-            Some(host.read_and_consume_nonce(&contract_id, &address)?)
-        } else {
-            None
+        let nonce = match (is_invoker, unordered_nonce) {
+            (true, _) => None,
+            (false, None) => Some(host.read_and_consume_nonce(contract_id, &address)?),
+            // Access the nonce entry the same way as consuming the nonce
+            // would, so that it's a part of the recorded footprint.
+            (false, Some(nonce)) => {
+                host.consume_unordered_nonce(contract_id, &address, nonce, true)?;
+                Some(nonce)
+            }
         };
         // Create the stack of `None` leading to the current invocation to
         // represent invocations that didn't need authorization on behalf of
@@ -604,14 +965,43 @@ impl AuthorizationTracker {
             need_nonce: false,
             is_invoker,
             nonce,
+            signature_expiration_ledger: nonce.and_then(nonce_signature_expiration_ledger),
+            simulate_signatures: false,
         })
     }
 
+    // Creates a tracker for the invocation tree authorized by the contract on
+    // its own behalf at the given call stack depth. The contract doesn't need
+    // to be authenticated, as it is the one making the authorization.
+    fn new_invoker_contract(
+        contract_id: Hash,
+        invocation: AuthorizedInvocation,
+        call_stack_depth: usize,
+    ) -> Self {
+        Self {
+            address: Some(ScAddress::Contract(contract_id)),
+            root_authorized_invocation: invocation,
+            // None of the frames up to and including the authorizing contract
+            // are part of the authorized tree.
+            invocation_id_in_call_stack: vec![None; call_stack_depth],
+            signature_args: Default::default(),
+            is_valid: true,
+            authenticated: true,
+            need_nonce: false,
+            is_invoker: false,
+            nonce: None,
+            signature_expiration_ledger: None,
+            simulate_signatures: false,
+        }
+    }
+
     // Tries to find and enforce the provided invocation with this tracker and
     // lazily performs authentication when needed.
     // This is needed for the enforcing mode only.
     // This assumes that the address matching is correctly performed before
     // calling this.
+    // Classic accounts are authenticated against the `threshold` level of the
+    // account.
     // Returns true/false based on whether the invocation is found in the
     // tracker. Returns error if invocation has been found, but the tracker
     // itself is not valid (failed authentication or nonce check).
@@ -621,6 +1011,7 @@ impl AuthorizationTracker {
         contract_id: &Hash,
         function_name: Symbol,
         args: &ScVec,
+        threshold: ThresholdIndexes,
     ) -> Result<bool, HostError> {
         if !self.is_valid {
             return Ok(false);
@@ -640,7 +1031,7 @@ impl AuthorizationTracker {
         }
         if !self.authenticated {
             let authenticate_res = self
-                .authenticate(host)
+                .authenticate(host, threshold)
                 .and_then(|_| self.verify_nonce(host));
             if let Some(err) = authenticate_res.err() {
                 self.is_valid = false;
@@ -726,6 +1117,15 @@ impl AuthorizationTracker {
         }
         self.need_nonce = false;
         if let Some(addr) = &self.address {
+            if let Some(nonce) = self.nonce.filter(|n| is_unordered_nonce(*n)) {
+                host.consume_unordered_nonce(
+                    &self.root_authorized_invocation.contract_id,
+                    addr,
+                    nonce,
+                    false,
+                )?;
+                return Ok(Some(nonce));
+            }
             Ok(Some(host.read_and_consume_nonce(
                 &self.root_authorized_invocation.contract_id,
                 addr,
@@ -749,6 +1149,74 @@ impl AuthorizationTracker {
         None
     }
 
+    fn last_authorized_invocation(&self) -> Option<&AuthorizedInvocation> {
+        for i in 0..self.invocation_id_in_call_stack.len() {
+            if self.invocation_id_in_call_stack[i].is_some() {
+                return Some(
+                    self.root_authorized_invocation
+                        .last_authorized_invocation(&self.invocation_id_in_call_stack, i + 1),
+                );
+            }
+        }
+        None
+    }
+
+    fn address_to_host_val(&self, host: &Host) -> Result<RawVal, HostError> {
+        Ok(match &self.address {
+            Some(addr) => host
+                .add_host_object(addr.metered_clone(host.budget_ref())?)?
+                .into(),
+            None => RawVal::from_void(),
+        })
+    }
+
+    // Explains why this tracker can't authorize the provided invocation on
+    // behalf of `address`. Only meant for diagnostics after
+    // `maybe_authorize_invocation` has failed to match the invocation.
+    // Returns the mismatch reason and, in case of arguments mismatch, the
+    // authorized arguments of the closest invocation.
+    fn diagnose_mismatch(
+        &self,
+        address: &ScAddress,
+        contract_id: &Hash,
+        function_name: Symbol,
+        args: &ScVec,
+    ) -> (AuthMismatchReason, Option<ScVec>) {
+        if self.address.as_ref() != Some(address) {
+            return (AuthMismatchReason::AddressMismatch, None);
+        }
+        if !self.is_valid {
+            return (AuthMismatchReason::Invalidated, None);
+        }
+        if let Some(Some(_)) = self.invocation_id_in_call_stack.last() {
+            return (AuthMismatchReason::FrameAlreadyAuthorized, None);
+        }
+        let candidates: &[AuthorizedInvocation] = if self.has_authorized_invocations_in_stack() {
+            match self.last_authorized_invocation() {
+                Some(curr_invocation) => &curr_invocation.sub_invocations,
+                None => &[],
+            }
+        } else {
+            std::slice::from_ref(&self.root_authorized_invocation)
+        };
+        let mut result = (AuthMismatchReason::NoMatchingInvocation, None);
+        for candidate in candidates {
+            if &candidate.contract_id != contract_id || candidate.function_name != function_name {
+                continue;
+            }
+            if &candidate.args != args {
+                result = (
+                    AuthMismatchReason::ArgsMismatch,
+                    Some(candidate.args.clone()),
+                );
+            } else if candidate.is_exhausted {
+                // An exact match takes precedence over the arguments mismatch.
+                return (AuthMismatchReason::InvocationExhausted, None);
+            }
+        }
+        result
+    }
+
     // Tries to match the provided invocation to the authorized sub-invocation
     // of the current tree and push it to the call stack.
     // Returns `true` if the match has been found.
@@ -789,53 +1257,80 @@ impl AuthorizationTracker {
     }
 
     fn verify_nonce(&mut self, host: &Host) -> Result<(), HostError> {
-        let nonce_is_correct = if let Some(nonce) = self.maybe_consume_nonce(host)? {
-            if let Some(tracker_nonce) = self.nonce {
-                tracker_nonce == nonce
-            } else {
-                // If the nonce isn't set in the tracker, but is required, then
-                // it's incorrect.
-                false
-            }
-        } else {
-            // Nonce is either already checked or not needed in the first place.
-            true
+        // `None` means that the nonce is either already checked or not needed
+        // in the first place.
+        let stored_nonce = match self.maybe_consume_nonce(host)? {
+            Some(nonce) => nonce,
+            None => return Ok(()),
         };
-        if nonce_is_correct {
-            Ok(())
-        } else {
-            Err(ScHostAuthErrorCode::NonceError.into())
+        // If the nonce isn't set in the tracker, but is required, then it's
+        // incorrect.
+        if self.nonce == Some(stored_nonce) {
+            return Ok(());
         }
+        let tracker_nonce = match self.nonce {
+            Some(n) => host.to_host_val(&ScVal::Object(Some(ScObject::U64(n))))?,
+            None => RawVal::from_void(),
+        };
+        Err(host.err_status_msg_with_args(
+            ScHostAuthErrorCode::NonceError,
+            "nonce mismatch for address {}: authorized nonce {} doesn't match the stored nonce {}",
+            &[
+                self.address_to_host_val(host)?,
+                tracker_nonce,
+                host.to_host_val(&ScVal::Object(Some(ScObject::U64(stored_nonce))))?,
+            ],
+        ))
+    }
+
+    fn get_nonce(&self, host: &Host) -> Result<u64, HostError> {
+        self.nonce
+            .ok_or_else(|| host.err_general("unexpected missing nonce"))
     }
 
     // Computes the payload that has to be signed in order to authenticate
     // the authorized invocation tree corresponding to this tracker.
     fn get_signature_payload(&self, host: &Host) -> Result<[u8; 32], HostError> {
-        let payload_preimage = HashIdPreimage::ContractAuth(HashIdPreimageContractAuth {
-            network_id: Hash(
-                host.with_ledger_info(|li| li.network_id.metered_clone(host.budget_ref()))?,
-            ),
-            invocation: self.invocation_to_xdr(host.budget_ref())?,
-            nonce: self
-                .nonce
-                .clone()
-                .ok_or(host.err_general("unexpected missing nonce"))?,
-        });
+        host.get_authorization_signature_payload(
+            self.invocation_to_xdr(host.budget_ref())?,
+            self.get_nonce(host)?,
+        )
+    }
 
-        host.metered_hash_xdr(&payload_preimage)
+    // Fails if the signature expiration ledger has already passed.
+    fn check_signature_expiration(&self, host: &Host) -> Result<(), HostError> {
+        if let Some(expiration_ledger) = self.signature_expiration_ledger {
+            let ledger_seq = host.with_ledger_info(|li| Ok(li.sequence_number))?;
+            if ledger_seq > expiration_ledger {
+                return Err(host.err_status_msg_with_args(
+                    ScHostAuthErrorCode::NotAuthorized,
+                    "signature has expired at ledger {}, current ledger is {}",
+                    &[expiration_ledger.into(), ledger_seq.into()],
+                ));
+            }
+        }
+        Ok(())
     }
 
-    fn authenticate(&self, host: &Host) -> Result<(), HostError> {
+    fn authenticate(&self, host: &Host, threshold: ThresholdIndexes) -> Result<(), HostError> {
         if self.is_invoker {
             return Ok(());
         }
+        self.check_signature_expiration(host)?;
         if let Some(address) = &self.address {
-            // TODO: there should also be a mode where a dummy payload is used
-            // instead (for enforcing mode preflight).
             let payload = self.get_signature_payload(host)?;
+            if self.simulate_signatures {
+                return self.simulate_authentication(host, address, &payload, threshold);
+            }
             match address {
                 ScAddress::Account(acc) => {
-                    check_account_authentication(host, &acc, &payload, &self.signature_args)?;
+                    check_account_authentication(
+                        host,
+                        acc,
+                        &payload,
+                        &self.signature_args,
+                        threshold,
+                    )?;
                 }
                 ScAddress::Contract(acc_contract) => {
                     check_account_contract_auth(
@@ -844,6 +1339,8 @@ impl AuthorizationTracker {
                         &payload,
                         &self.signature_args,
                         &self.root_authorized_invocation,
+                        self.get_nonce(host)?,
+                        self.signature_expiration_ledger,
                     )?;
                 }
             }
@@ -853,6 +1350,32 @@ impl AuthorizationTracker {
         }
     }
 
+    // Emulates authentication for the enforcing mode preflight. The real
+    // payload is authenticated, but the signatures are not verified, so that
+    // the budget is charged as close as possible to the real authentication.
+    fn simulate_authentication(
+        &self,
+        host: &Host,
+        address: &ScAddress,
+        payload: &[u8; 32],
+        threshold: ThresholdIndexes,
+    ) -> Result<(), HostError> {
+        match address {
+            ScAddress::Account(acc) => {
+                simulate_account_authentication(host, acc, payload, threshold)
+            }
+            ScAddress::Contract(acc_contract) => simulate_account_contract_auth(
+                host,
+                acc_contract,
+                payload,
+                &self.signature_args,
+                &self.root_authorized_invocation,
+                self.get_nonce(host)?,
+                self.signature_expiration_ledger,
+            ),
+        }
+    }
+
     // Emulates authentication for the recording mode.
     fn emulate_authentication(&self, host: &Host) -> Result<(), HostError> {
         if self.is_invoker {
@@ -863,7 +1386,7 @@ impl AuthorizationTracker {
             let _payload = self.get_signature_payload(host)?;
             match address {
                 ScAddress::Account(acc) => {
-                    let _account = host.load_account(acc.clone())?;
+                    let _account = host.load_account_signers(acc)?;
                 }
                 // Skip custom accounts for now - emulating authentication for
                 // them requires a dummy signature.
@@ -877,6 +1400,62 @@ impl AuthorizationTracker {
 }
 
 impl Host {
+    // Converts the `InvokerContractAuthEntry` vector passed to
+    // `authorize_as_curr_contract` and registers the authorizations on behalf
+    // of the current contract.
+    pub(crate) fn authorize_as_curr_contract_internal(
+        &self,
+        auth_entries: RawVal,
+    ) -> Result<(), HostError> {
+        let contract_id = self.get_current_contract_id_internal()?;
+        let entries = crate::native_contract::base_types::Vec::try_from_val(self, &auth_entries)?;
+        let mut invocations = vec![];
+        for i in 0..entries.len()? {
+            let entry: InvokerContractAuthEntry = entries.get(i)?;
+            invocations.push(AuthorizedInvocation::from_invoker_contract_entry(
+                self, entry,
+            )?);
+        }
+        self.0
+            .authorization_manager
+            .borrow_mut()
+            .add_invoker_contract_auth(self, contract_id, invocations)
+    }
+
+    // Builds the `AuthPolicyContext` of the `check_auth` call that is
+    // currently running in the current contract.
+    pub(crate) fn get_auth_policy_context_internal(&self) -> Result<Object, HostError> {
+        let frame = self.current_check_auth_frame().ok_or_else(|| {
+            self.err_status_msg(
+                ScHostFnErrorCode::InputArgsInvalid,
+                "auth policy context is only available in `check_auth`",
+            )
+        })?;
+        let context = frame.to_policy_context(self)?;
+        Ok(RawVal::try_from_val(self, &context)?.try_into()?)
+    }
+
+    /// Computes the payload that has to be signed by an address in order to
+    /// authorize `invocation` with the given `nonce` on the current network.
+    ///
+    /// The payload is the SHA-256 hash of the XDR-serialized
+    /// `HashIdPreimage::ContractAuth`. For the unordered nonces this includes
+    /// the signature expiration ledger encoded in the nonce.
+    pub fn get_authorization_signature_payload(
+        &self,
+        invocation: xdr::AuthorizedInvocation,
+        nonce: u64,
+    ) -> Result<[u8; 32], HostError> {
+        let payload_preimage = HashIdPreimage::ContractAuth(HashIdPreimageContractAuth {
+            network_id: Hash(
+                self.with_ledger_info(|li| li.network_id.metered_clone(self.budget_ref()))?,
+            ),
+            invocation,
+            nonce,
+        });
+        self.metered_hash_xdr(&payload_preimage)
+    }
+
     #[cfg(test)]
     pub(crate) fn read_nonce(
         &self,
@@ -952,4 +1531,72 @@ impl Host {
         self.with_mut_storage(|storage| storage.put(&nonce_key, &entry, self.budget_ref()))?;
         Ok(curr_nonce)
     }
+
+    // Consumes an unordered nonce for the given contract and address, failing
+    // if it has been consumed already.
+    // Every consumed unordered nonce is stored in its own entry, so consuming
+    // a nonce only accesses a single small entry, no matter how many nonces
+    // the address has consumed.
+    // When `record_only` is set (in the recording mode) the entry is recorded
+    // in the footprint as if it was written, but the nonce is neither checked
+    // nor stored.
+    pub(crate) fn consume_unordered_nonce(
+        &self,
+        contract_id: &Hash,
+        address: &ScAddress,
+        nonce: u64,
+        record_only: bool,
+    ) -> Result<(), HostError> {
+        let expiration_ledger = nonce_signature_expiration_ledger(nonce)
+            .ok_or_else(|| self.err_general("unexpected sequential nonce"))?;
+        // The key wraps a `NonceKey`, which contracts can't construct, so
+        // the entry can't be modified by contracts.
+        let nonce_key_scval = ScVal::Object(Some(ScObject::Vec(
+            self.map_err(
+                vec![
+                    ScVal::Object(Some(ScObject::NonceKey(
+                        address.metered_clone(self.budget_ref())?,
+                    ))),
+                    ScVal::Object(Some(ScObject::U64(nonce))),
+                ]
+                .try_into(),
+            )?,
+        )));
+        let nonce_key = self.storage_key_for_contract(
+            contract_id.metered_clone(self.budget_ref())?,
+            nonce_key_scval.clone(),
+        );
+        if record_only {
+            return self.with_mut_storage(|storage| {
+                if let FootprintMode::Recording(_) = storage.mode {
+                    storage.footprint.record_access(
+                        &nonce_key,
+                        AccessType::ReadWrite,
+                        self.budget_ref(),
+                    )?;
+                }
+                Ok(())
+            });
+        }
+        if self.with_mut_storage(|storage| storage.has(&nonce_key, self.budget_ref()))? {
+            return Err(self.err_status_msg(
+                ScHostAuthErrorCode::NonceError,
+                "unordered nonce has already been consumed",
+            ));
+        }
+        // Store the expiration ledger, after which the entry is no longer
+        // needed for the replay protection.
+        let data = LedgerEntryData::ContractData(ContractDataEntry {
+            contract_id: contract_id.metered_clone(self.budget_ref())?,
+            key: nonce_key_scval,
+            val: ScVal::U32(expiration_ledger),
+        });
+        let entry = LedgerEntry {
+            last_modified_ledger_seq: 0,
+            data,
+            ext: LedgerEntryExt::V0,
+        };
+        self.with_mut_storage(|storage| storage.put(&nonce_key, &entry, self.budget_ref()))?;
+        Ok(())
+    }
 }
@@ -0,0 +1,182 @@
+//! Export of the authorization trees tracked by the host, meant for showing
+//! the user what exactly they are about to sign (or what has been authorized)
+//! in a structured form: JSON for the programmatic consumers and Graphviz DOT
+//! for rendering.
+//!
+//! Addresses and contract ids are rendered as strkeys (`G...` for the accounts
+//! and `C...` for the contracts) and arguments with [PrettyScVal], the same
+//! way as in the rendered host events.
+
+use std::fmt::Write;
+
+use soroban_env_common::xdr::{Hash, ScAddress, ScVal};
+
+use super::{AuthorizationTracker, AuthorizedInvocation};
+use crate::events::{address_to_strkey, contract_id_to_strkey, PrettyScVal};
+use crate::json;
+
+/// Invocation authorized by an address along with all of its authorized
+/// sub-invocations.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuthorizationTreeNode {
+    pub contract_id: Hash,
+    pub function_name: String,
+    pub args: Vec<ScVal>,
+    /// Whether the invocation has already been authorized (and hence can't
+    /// be authorized again). Recorded invocations are always exhausted.
+    pub is_exhausted: bool,
+    pub sub_invocations: Vec<AuthorizationTreeNode>,
+}
+
+/// Authorization tree of a single address.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuthorizationTree {
+    /// `None` for the transaction invoker that doesn't need to sign the
+    /// authorization separately.
+    pub address: Option<ScAddress>,
+    pub nonce: Option<u64>,
+    pub root: AuthorizationTreeNode,
+}
+
+impl AuthorizationTreeNode {
+    fn from_invocation(invocation: &AuthorizedInvocation) -> Self {
+        Self {
+            contract_id: invocation.contract_id.clone(),
+            function_name: invocation.function_name.to_string(),
+            args: invocation.args.0.to_vec(),
+            is_exhausted: invocation.is_exhausted,
+            sub_invocations: invocation
+                .sub_invocations
+                .iter()
+                .map(Self::from_invocation)
+                .collect(),
+        }
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"contract_id\":");
+        json::write_string(out, &contract_id_to_strkey(&self.contract_id));
+        out.push_str(",\"function_name\":");
+        json::write_string(out, &self.function_name);
+        out.push_str(",\"args\":");
+        json::write_array(out, &self.args, |out, arg| {
+            json::write_string(out, &PrettyScVal(arg).to_string())
+        });
+        let _ = write!(out, ",\"is_exhausted\":{}", self.is_exhausted);
+        out.push_str(",\"sub_invocations\":");
+        json::write_array(out, &self.sub_invocations, |out, sub_invocation| {
+            sub_invocation.write_json(out)
+        });
+        out.push('}');
+    }
+
+    // Writes the node and all of its descendants, returns the id of the node.
+    fn write_dot(&self, out: &mut String, id_prefix: &str, next_id: &mut usize) -> String {
+        let id = format!("{}_{}", id_prefix, next_id);
+        *next_id += 1;
+        let args = self
+            .args
+            .iter()
+            .map(|arg| PrettyScVal(arg).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let label = format!(
+            "{}.{}({})",
+            contract_id_to_strkey(&self.contract_id),
+            self.function_name,
+            args
+        );
+        let _ = writeln!(
+            out,
+            "    {} [label=\"{}\", style=\"{}\"];",
+            id,
+            escape_dot(&label),
+            if self.is_exhausted { "solid" } else { "dashed" }
+        );
+        for sub_invocation in &self.sub_invocations {
+            let sub_id = sub_invocation.write_dot(out, id_prefix, next_id);
+            let _ = writeln!(out, "    {} -> {};", id, sub_id);
+        }
+        id
+    }
+}
+
+impl AuthorizationTree {
+    pub(super) fn from_tracker(tracker: &AuthorizationTracker) -> Self {
+        Self {
+            address: if tracker.is_invoker {
+                None
+            } else {
+                tracker.address.clone()
+            },
+            nonce: tracker.nonce,
+            root: AuthorizationTreeNode::from_invocation(&tracker.root_authorized_invocation),
+        }
+    }
+
+    /// Renders the tree as a JSON object with `address`, `nonce` and `root`
+    /// fields. Every node is an object with `contract_id`, `function_name`,
+    /// `args`, `is_exhausted` and `sub_invocations` fields.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    /// Renders the tree as a Graphviz DOT graph. The invocations that haven't
+    /// been exhausted yet are drawn with dashed borders.
+    pub fn to_dot(&self) -> String {
+        authorization_trees_to_dot(std::slice::from_ref(self))
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"address\":");
+        json::write_opt_string(out, self.address.as_ref().map(address_to_strkey).as_deref());
+        out.push_str(",\"nonce\":");
+        match self.nonce {
+            Some(nonce) => {
+                let _ = write!(out, "{}", nonce);
+            }
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"root\":");
+        self.root.write_json(out);
+        out.push('}');
+    }
+}
+
+/// Renders the trees as a JSON array of the objects produced by
+/// [AuthorizationTree::to_json].
+pub fn authorization_trees_to_json(trees: &[AuthorizationTree]) -> String {
+    let mut out = String::new();
+    json::write_array(&mut out, trees, |out, tree| tree.write_json(out));
+    out
+}
+
+/// Renders the trees as a single Graphviz DOT graph with a cluster per
+/// address.
+pub fn authorization_trees_to_dot(trees: &[AuthorizationTree]) -> String {
+    let mut out = String::from("digraph authorizations {\n  node [shape=box];\n");
+    for (i, tree) in trees.iter().enumerate() {
+        let address = match &tree.address {
+            Some(address) => address_to_strkey(address),
+            None => "invoker".to_string(),
+        };
+        let label = match tree.nonce {
+            Some(nonce) => format!("{} (nonce {})", address, nonce),
+            None => address,
+        };
+        let _ = writeln!(out, "  subgraph cluster_{} {{", i);
+        let _ = writeln!(out, "    label=\"{}\";", escape_dot(&label));
+        let mut next_id = 0;
+        tree.root
+            .write_dot(&mut out, &format!("n{}", i), &mut next_id);
+        out.push_str("  }\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,174 @@
+//! Helpers for turning the payloads recorded in the recording authorization
+//! mode into signed [ContractAuth] entries that can be passed to
+//! [Host::set_authorization_entries].
+
+use ed25519_dalek::{Keypair, Signer};
+use soroban_env_common::xdr::{
+    AccountId, AddressWithNonce, ContractAuth, Hash, PublicKey, ScAddress, ScVec, Uint256,
+};
+use soroban_env_common::{EnvBase, TryFromVal};
+
+use crate::native_contract::account_contract::AccountEd25519Signature;
+use crate::native_contract::base_types::{BytesN, Vec as HostVec};
+use crate::{Host, HostError};
+
+use super::RecordedAuthPayload;
+
+/// Produces the `signature_args` of a custom account contract for the given
+/// signature payload. The args are passed to the account's `check_auth`
+/// function as is.
+pub type ContractSignFn<'a> = dyn Fn(&Host, &[u8; 32]) -> Result<ScVec, HostError> + 'a;
+
+/// Signer of the recorded authorization payloads of a single address.
+pub enum AuthSigner<'a> {
+    /// Classic Stellar account signed by any subset of its ed25519 signers
+    /// (including the master key). The signers must have enough weight to
+    /// reach the account threshold required by the invocation (medium for
+    /// most of the operations).
+    Account {
+        account_id: AccountId,
+        signers: Vec<&'a Keypair>,
+    },
+    /// Custom account contract.
+    Contract {
+        contract_id: Hash,
+        sign: Box<ContractSignFn<'a>>,
+    },
+}
+
+impl<'a> AuthSigner<'a> {
+    /// Classic account signed only by its master key.
+    pub fn ed25519(key: &'a Keypair) -> Self {
+        AuthSigner::Account {
+            account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+                key.public.to_bytes(),
+            ))),
+            signers: vec![key],
+        }
+    }
+
+    /// Classic account signed by the given subset of its signers.
+    pub fn account_multisig(account_id: AccountId, signers: Vec<&'a Keypair>) -> Self {
+        AuthSigner::Account {
+            account_id,
+            signers,
+        }
+    }
+
+    /// Custom account contract with the given signing function.
+    pub fn contract(
+        contract_id: Hash,
+        sign: impl Fn(&Host, &[u8; 32]) -> Result<ScVec, HostError> + 'a,
+    ) -> Self {
+        AuthSigner::Contract {
+            contract_id,
+            sign: Box::new(sign),
+        }
+    }
+
+    pub fn address(&self) -> ScAddress {
+        match self {
+            AuthSigner::Account { account_id, .. } => ScAddress::Account(account_id.clone()),
+            AuthSigner::Contract { contract_id, .. } => ScAddress::Contract(contract_id.clone()),
+        }
+    }
+
+    fn sign(&self, host: &Host, payload: &[u8; 32]) -> Result<ScVec, HostError> {
+        match self {
+            AuthSigner::Account { signers, .. } => {
+                let mut signers = signers.clone();
+                signers.sort_by_key(|k| k.public.to_bytes());
+                host.call_args_to_scvec(account_signature_args(host, &signers, payload)?.into())
+            }
+            AuthSigner::Contract { sign, .. } => sign(host, payload),
+        }
+    }
+}
+
+// Signs `payload` with an ed25519 key.
+pub(crate) fn sign_payload_for_ed25519(
+    host: &Host,
+    signer: &Keypair,
+    payload: &[u8],
+) -> Result<BytesN<64>, HostError> {
+    BytesN::<64>::try_from_val(
+        host,
+        &host.bytes_new_from_slice(&signer.sign(payload).to_bytes())?,
+    )
+}
+
+// Signs `payload` with one of the ed25519 signers of a classic account.
+pub(crate) fn sign_payload_for_account(
+    host: &Host,
+    signer: &Keypair,
+    payload: &[u8],
+) -> Result<AccountEd25519Signature, HostError> {
+    Ok(AccountEd25519Signature {
+        public_key: BytesN::<32>::try_from_val(
+            host,
+            &host.bytes_new_from_slice(&signer.public.to_bytes())?,
+        )?,
+        signature: sign_payload_for_ed25519(host, signer, payload)?,
+    })
+}
+
+// Builds the `signature_args` of a classic account signed by `signers`: a
+// single vector of their signatures, in the order of `signers`. The account
+// contract requires the signers to be ordered by the public key.
+pub(crate) fn account_signature_args(
+    host: &Host,
+    signers: &[&Keypair],
+    payload: &[u8],
+) -> Result<HostVec, HostError> {
+    let mut signatures = HostVec::new(host)?;
+    for key in signers {
+        signatures.push(&sign_payload_for_account(host, key, payload)?)?;
+    }
+    let mut signature_args = HostVec::new(host)?;
+    signature_args.push(&signatures)?;
+    Ok(signature_args)
+}
+
+/// Signs every recorded payload with the signer of its address and returns
+/// the resulting authorization entries, ready to be passed to
+/// [Host::set_authorization_entries].
+///
+/// Payloads without an address belong to the transaction invoker and are
+/// returned without signatures. Fails if there is no signer for any other
+/// recorded address.
+pub fn sign_recorded_payloads(
+    host: &Host,
+    payloads: &[RecordedAuthPayload],
+    signers: &[AuthSigner],
+) -> Result<Vec<ContractAuth>, HostError> {
+    let mut entries = vec![];
+    for payload in payloads {
+        let (address_with_nonce, signature_args) = match &payload.address {
+            None => (None, ScVec::default()),
+            Some(address) => {
+                let signer = signers
+                    .iter()
+                    .find(|s| &s.address() == address)
+                    .ok_or_else(|| host.err_general("no signer for the recorded address"))?;
+                let nonce = payload
+                    .nonce
+                    .ok_or_else(|| host.err_general("unexpected missing recorded nonce"))?;
+                let signature_payload =
+                    host.get_authorization_signature_payload(payload.invocation.clone(), nonce)?;
+                (
+                    Some(AddressWithNonce {
+                        address: address.clone(),
+                        nonce,
+                    }),
+                    signer.sign(host, &signature_payload)?,
+                )
+            }
+        };
+        entries.push(ContractAuth {
+            address_with_nonce,
+            root_invocation: payload.invocation.clone(),
+            signature_args,
+        });
+    }
+    Ok(entries)
+}
@@ -5,6 +5,16 @@ use std::{
 
 use crate::{xdr::ScVmErrorCode, Host, HostError};
 
+mod profile;
+pub use profile::{BudgetProfile, ProfileEntry};
+
+#[cfg(any(test, feature = "testutils"))]
+mod alloc_check;
+#[cfg(any(test, feature = "testutils"))]
+pub use alloc_check::{
+    AllocationCounter, AllocationDiscrepancy, AllocationReport, TrackingAllocationCounter,
+};
+
 // TODO: move this to an XDR enum
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -95,6 +105,12 @@ pub enum CostType {
     // here for exploring calibration, not a long-term cost we surface
     // separately from signature verification.
     EdwardsPointCurve25519ScalarMul = 35,
+    // Cost of decoding an ECDSA secp256r1 (P-256) public key from its SEC1
+    // encoding, which includes checking that the point is on the curve.
+    ComputeEcdsaSecp256r1PubKey = 36,
+    // Cost of verifying an ECDSA secp256r1 (P-256) signature of a payload,
+    // including hashing the payload with SHA-256.
+    VerifyEcdsaSecp256r1Sig = 37,
 }
 
 // TODO: add XDR support for iterating over all the elements of an enum
@@ -137,6 +153,8 @@ impl CostType {
             CostType::BytesCmp,
             CostType::ChargeBudget,
             CostType::EdwardsPointCurve25519ScalarMul,
+            CostType::ComputeEcdsaSecp256r1PubKey,
+            CostType::VerifyEcdsaSecp256r1Sig,
         ];
         VARIANTS.iter()
     }
@@ -248,6 +266,13 @@ pub struct BudgetDimension {
     /// Tracks the sum of _output_ values from the cost model, for purposes
     /// of comparing to limit.
     count: u64,
+
+    /// The limit and count of the shadow dimension, which is charged instead
+    /// of the main one while the budget is in shadow mode (see
+    /// [`Budget::with_shadow_mode`]). The shadow dimension uses the same cost
+    /// models but never contributes to `count`.
+    shadow_limit: u64,
+    shadow_count: u64,
 }
 
 impl BudgetDimension {
@@ -257,6 +282,8 @@ impl BudgetDimension {
             cost_models: Default::default(),
             limit: Default::default(),
             count: Default::default(),
+            shadow_limit: Default::default(),
+            shadow_count: Default::default(),
         };
         for _ct in CostType::variants() {
             // TODO: load cost model for i from the chain.
@@ -290,6 +317,23 @@ impl BudgetDimension {
         self.count > self.limit
     }
 
+    pub fn get_shadow_count(&self) -> u64 {
+        self.shadow_count
+    }
+
+    pub fn get_shadow_limit(&self) -> u64 {
+        self.shadow_limit
+    }
+
+    pub fn reset_shadow(&mut self, limit: u64) {
+        self.shadow_limit = limit;
+        self.shadow_count = 0;
+    }
+
+    pub fn is_over_shadow_budget(&self) -> bool {
+        self.shadow_count > self.shadow_limit
+    }
+
     pub fn charge(&mut self, ty: CostType, input: u64) -> Result<(), HostError> {
         let cm = self.get_cost_model(ty);
         self.count = self.count.saturating_add(cm.evaluate(input));
@@ -301,6 +345,16 @@ impl BudgetDimension {
         }
     }
 
+    pub fn shadow_charge(&mut self, ty: CostType, input: u64) -> Result<(), HostError> {
+        let cm = self.get_cost_model(ty);
+        self.shadow_count = self.shadow_count.saturating_add(cm.evaluate(input));
+        if self.is_over_shadow_budget() {
+            Err(self.trapcode.into())
+        } else {
+            Ok(())
+        }
+    }
+
     // Resets all model parameters to zero (so that we can override and test individual ones later).
     #[cfg(test)]
     pub fn reset_models(&mut self) {
@@ -317,6 +371,18 @@ pub(crate) struct BudgetImpl {
     /// Tracks the sums of _input_ values to the cost models, for purposes of
     /// calibration and reporting; not used for budget-limiting per se.
     inputs: Vec<u64>,
+    /// Optional timeline of every charge, see [`Budget::enable_profiling`].
+    profile: Option<BudgetProfile>,
+    /// Number of times profiling has been enabled, used to tell the profiles
+    /// apart.
+    profiles_enabled: u64,
+    /// Optional cross-check of charged memory against real allocations, see
+    /// [`Budget::enable_allocation_check`].
+    #[cfg(any(test, feature = "testutils"))]
+    alloc_check: Option<alloc_check::AllocationCheck>,
+    /// When set, charges go to the shadow half of each dimension rather than
+    /// the main one. See [`Budget::with_shadow_mode`].
+    in_shadow_mode: bool,
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -356,14 +422,65 @@ impl Budget {
         // important to buffer WASM step counts before flushing to budgeting,
         // and we add a constant charge here for "the cost of budget-counting"
         // itself.
+        if self.0.borrow().in_shadow_mode {
+            return self.mut_budget(|mut b| {
+                b.cpu_insns.shadow_charge(CostType::ChargeBudget, 1)?;
+                b.cpu_insns.shadow_charge(ty, input)?;
+                b.mem_bytes.shadow_charge(ty, input)
+            });
+        }
         self.get_input_mut(ty, |i| *i = i.saturating_add(input));
         self.mut_budget(|mut b| {
             b.cpu_insns.charge(CostType::ChargeBudget, 1)?;
             b.cpu_insns.charge(ty, input)?;
-            b.mem_bytes.charge(ty, input)
+            #[cfg(any(test, feature = "testutils"))]
+            let mem_before = b.mem_bytes.get_count();
+            b.mem_bytes.charge(ty, input)?;
+            #[cfg(any(test, feature = "testutils"))]
+            {
+                let charged = b.mem_bytes.get_count().saturating_sub(mem_before);
+                if let Some(c) = b.alloc_check.as_mut() {
+                    c.observe_charge(ty, charged)
+                }
+            }
+            let (cpu, mem) = (b.cpu_insns.get_count(), b.mem_bytes.get_count());
+            if let Some(p) = b.profile.as_mut() {
+                p.record(ty, input, cpu, mem)
+            }
+            Ok(())
         })
     }
 
+    /// Runs `f` with all charges redirected to the shadow budget, which
+    /// meters diagnostic work (such as debug events) against its own limits.
+    /// Work done in shadow mode never changes the main counts, so whether a
+    /// contract runs out of budget does not depend on diagnostics being
+    /// enabled. Exceeding the shadow limits produces the usual budget errors,
+    /// which callers are expected to handle by dropping the diagnostic work.
+    pub fn with_shadow_mode<T, F>(&self, f: F) -> Result<T, HostError>
+    where
+        F: FnOnce() -> Result<T, HostError>,
+    {
+        let prev = std::mem::replace(&mut self.0.borrow_mut().in_shadow_mode, true);
+        let res = f();
+        self.0.borrow_mut().in_shadow_mode = prev;
+        res
+    }
+
+    pub fn get_shadow_cpu_insns_count(&self) -> u64 {
+        self.0.borrow().cpu_insns.get_shadow_count()
+    }
+
+    pub fn get_shadow_mem_bytes_count(&self) -> u64 {
+        self.0.borrow().mem_bytes.get_shadow_count()
+    }
+
+    pub fn reset_shadow_limits(&self, cpu: u64, mem: u64) {
+        let mut b = self.0.borrow_mut();
+        b.cpu_insns.reset_shadow(cpu);
+        b.mem_bytes.reset_shadow(mem);
+    }
+
     pub fn get_input(&self, ty: CostType) -> u64 {
         self.0.borrow().inputs[ty as usize]
     }
@@ -375,6 +492,22 @@ impl Budget {
         f(&mut self.0.borrow_mut().inputs[ty as usize])
     }
 
+    /// Returns the cpu and memory cost models for `ty`.
+    pub fn get_cost_models(&self, ty: CostType) -> (CostModel, CostModel) {
+        let b = self.0.borrow();
+        (
+            b.cpu_insns.get_cost_model(ty).clone(),
+            b.mem_bytes.get_cost_model(ty).clone(),
+        )
+    }
+
+    /// Replaces the cpu and memory cost models for `ty`.
+    pub fn set_cost_models(&self, ty: CostType, cpu: CostModel, mem: CostModel) {
+        let mut b = self.0.borrow_mut();
+        *b.cpu_insns.get_cost_model_mut(ty) = cpu;
+        *b.mem_bytes.get_cost_model_mut(ty) = mem;
+    }
+
     pub fn get_cpu_insns_count(&self) -> u64 {
         self.0.borrow().cpu_insns.get_count()
     }
@@ -444,6 +577,11 @@ impl Default for BudgetImpl {
             cpu_insns: BudgetDimension::new(ScVmErrorCode::TrapCpuLimitExceeded),
             mem_bytes: BudgetDimension::new(ScVmErrorCode::TrapMemLimitExceeded),
             inputs: Default::default(),
+            profile: None,
+            profiles_enabled: 0,
+            #[cfg(any(test, feature = "testutils"))]
+            alloc_check: None,
+            in_shadow_mode: false,
         };
 
         for ct in CostType::variants() {
@@ -497,6 +635,11 @@ impl Default for BudgetImpl {
                 CostType::BytesCmp => cpu.lin_param = 1,
                 CostType::ChargeBudget => cpu.const_param = 50,
                 CostType::EdwardsPointCurve25519ScalarMul => cpu.const_param = 10,
+                CostType::ComputeEcdsaSecp256r1PubKey => cpu.const_param = 135_000,
+                CostType::VerifyEcdsaSecp256r1Sig => {
+                    cpu.const_param = 3_115_000;
+                    cpu.lin_param = 54;
+                }
             }
 
             let mem = b.mem_bytes.get_cost_model_mut(*ct);
@@ -532,6 +675,7 @@ impl Default for BudgetImpl {
                 CostType::BytesCmp => (),
                 CostType::ChargeBudget => (),
                 CostType::EdwardsPointCurve25519ScalarMul => mem.const_param = 1,
+                CostType::ComputeEcdsaSecp256r1PubKey | CostType::VerifyEcdsaSecp256r1Sig => (),
             }
         }
 
@@ -551,6 +695,10 @@ impl Default for BudgetImpl {
         // For now we set a generous but finite limit for DOS prevention.
         b.cpu_insns.reset(40_000_000); // 100x the estimation above which corresponds to 10ms
         b.mem_bytes.reset(0xa0_0000); // 10MB of memory
+
+        // Diagnostics get the same generous allowance, separately.
+        b.cpu_insns.reset_shadow(40_000_000);
+        b.mem_bytes.reset_shadow(0xa0_0000);
         b
     }
 }
@@ -0,0 +1,218 @@
+use std::{
+    cmp::Reverse,
+    hash::{Hash, Hasher},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use tracking_allocator::{AllocationGroupId, AllocationRegistry, AllocationTracker};
+
+use super::{Budget, CostType};
+
+/// A source of real allocation measurements to cross-check the modeled
+/// `mem_bytes` dimension against. Implementations return a monotonically
+/// non-decreasing count of bytes allocated so far.
+pub trait AllocationCounter {
+    fn allocated_bytes(&self) -> u64;
+}
+
+/// An [`AllocationCounter`] fed by the `tracking-allocator` crate, which the
+/// benchmarks also use to measure memory. To use it, install
+/// `tracking_allocator::Allocator` in a test binary with
+/// `#[global_allocator] static A: Allocator<System> = Allocator::system();`,
+/// call [`TrackingAllocationCounter::install`] once and pass the counter to
+/// [`Budget::enable_allocation_check`].
+///
+/// Like the `MemTracker` used in the benchmarks, deallocations are not
+/// subtracted: we are estimating the worst-case memory cost, which mimics an
+/// arena model in which nothing is freed until the very end.
+#[derive(Clone, Debug, Default)]
+pub struct TrackingAllocationCounter(Arc<AtomicU64>);
+
+impl TrackingAllocationCounter {
+    /// Sets the counter as the global tracker of `tracking-allocator` and
+    /// enables tracking. This fails if a global tracker has already been set.
+    pub fn install() -> Result<Self, tracking_allocator::SetTrackerError> {
+        let counter = Self::default();
+        AllocationRegistry::set_global_tracker(counter.clone())?;
+        AllocationRegistry::enable_tracking();
+        Ok(counter)
+    }
+}
+
+impl AllocationTracker for TrackingAllocationCounter {
+    fn allocated(
+        &self,
+        _addr: usize,
+        _object_size: usize,
+        wrapped_size: usize,
+        _group_id: AllocationGroupId,
+    ) {
+        self.0.fetch_add(wrapped_size as u64, Ordering::SeqCst);
+    }
+
+    fn deallocated(
+        &self,
+        _addr: usize,
+        _object_size: usize,
+        _wrapped_size: usize,
+        _source_group_id: AllocationGroupId,
+        _current_group_id: AllocationGroupId,
+    ) {
+        // No-Op, see the comment on the type.
+    }
+}
+
+impl AllocationCounter for TrackingAllocationCounter {
+    fn allocated_bytes(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Charged versus measured memory for a single cost type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllocationDiscrepancy {
+    pub cost_type: CostType,
+    pub charged_bytes: u64,
+    pub measured_bytes: u64,
+}
+
+impl AllocationDiscrepancy {
+    pub fn is_under_charged(&self) -> bool {
+        self.measured_bytes > self.charged_bytes
+    }
+
+    pub fn difference(&self) -> u64 {
+        self.measured_bytes.abs_diff(self.charged_bytes)
+    }
+}
+
+/// Result of an allocation cross-check: one entry per cost type that was
+/// either charged or had allocations attributed to it, sorted by descending
+/// [`AllocationDiscrepancy::difference`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllocationReport(pub Vec<AllocationDiscrepancy>);
+
+impl AllocationReport {
+    pub fn under_charged(&self) -> impl Iterator<Item = &AllocationDiscrepancy> {
+        self.0.iter().filter(|d| d.is_under_charged())
+    }
+}
+
+// A handle to the counter of a check. Two handles are the same if they refer
+// to the same counter.
+#[derive(Clone)]
+struct CounterRef(Rc<dyn AllocationCounter>);
+
+impl CounterRef {
+    fn addr(&self) -> usize {
+        Rc::as_ptr(&self.0) as *const u8 as usize
+    }
+}
+
+impl std::fmt::Debug for CounterRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CounterRef({:#x})", self.addr())
+    }
+}
+
+impl PartialEq for CounterRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+impl Eq for CounterRef {}
+
+impl PartialOrd for CounterRef {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CounterRef {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.addr().cmp(&other.addr())
+    }
+}
+
+impl Hash for CounterRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr().hash(state)
+    }
+}
+
+// Allocations between two charges are attributed to the cost type of the
+// earlier charge, since the host charges for an operation before performing
+// it.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct AllocationCheck {
+    counter: CounterRef,
+    last_reading: u64,
+    last_type: Option<CostType>,
+    charged: Vec<u64>,
+    measured: Vec<u64>,
+}
+
+impl AllocationCheck {
+    fn new(counter: Rc<dyn AllocationCounter>) -> Self {
+        let n = CostType::variants().len();
+        Self {
+            last_reading: counter.allocated_bytes(),
+            counter: CounterRef(counter),
+            last_type: None,
+            charged: vec![0; n],
+            measured: vec![0; n],
+        }
+    }
+
+    fn attribute_pending(&mut self) {
+        let reading = self.counter.0.allocated_bytes();
+        if let Some(ty) = self.last_type {
+            let delta = reading.saturating_sub(self.last_reading);
+            self.measured[ty as usize] = self.measured[ty as usize].saturating_add(delta);
+        }
+        self.last_reading = reading;
+    }
+
+    pub(crate) fn observe_charge(&mut self, ty: CostType, mem_bytes: u64) {
+        self.attribute_pending();
+        self.last_type = Some(ty);
+        self.charged[ty as usize] = self.charged[ty as usize].saturating_add(mem_bytes);
+    }
+
+    fn report(&mut self) -> AllocationReport {
+        self.attribute_pending();
+        let mut entries: Vec<AllocationDiscrepancy> = CostType::variants()
+            .map(|ty| AllocationDiscrepancy {
+                cost_type: *ty,
+                charged_bytes: self.charged[*ty as usize],
+                measured_bytes: self.measured[*ty as usize],
+            })
+            .filter(|d| d.charged_bytes != 0 || d.measured_bytes != 0)
+            .collect();
+        entries.sort_by_key(|d| Reverse(d.difference()));
+        AllocationReport(entries)
+    }
+}
+
+impl Budget {
+    /// Starts cross-checking the memory charged to this budget against real
+    /// allocations reported by `counter`. Any previous check is discarded.
+    pub fn enable_allocation_check(&self, counter: Rc<dyn AllocationCounter>) {
+        self.0.borrow_mut().alloc_check = Some(AllocationCheck::new(counter))
+    }
+
+    /// Stops the allocation cross-check and returns its report, attributing
+    /// any allocations since the last charge to the last charged cost type.
+    pub fn disable_allocation_check(&self) -> Option<AllocationReport> {
+        self.0
+            .borrow_mut()
+            .alloc_check
+            .take()
+            .map(|mut c| c.report())
+    }
+}
@@ -0,0 +1,184 @@
+use std::{cmp::Reverse, fmt::Write};
+
+use super::{Budget, CostType};
+use crate::json;
+
+/// A single successful `Budget::charge` call, as observed while profiling is
+/// enabled. The `cpu_insns` and `mem_bytes` fields are the _cumulative_
+/// budget counts immediately after the charge was applied, so the sequence of
+/// entries forms a timeline of budget consumption.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProfileEntry {
+    pub cost_type: CostType,
+    pub input: u64,
+    /// The innermost host function (as dispatched from a VM) that was running
+    /// when the charge happened, or `None` if the charge was made outside of
+    /// any VM-dispatched host function (eg. by the embedding environment or a
+    /// native contract invoked directly).
+    pub host_fn: Option<&'static str>,
+    pub cpu_insns: u64,
+    pub mem_bytes: u64,
+}
+
+/// Recorded sequence of budget charges, enabled by
+/// [`Budget::enable_profiling`]. Profiling itself is not metered: it is a
+/// diagnostic aid for contract authors and should not be enabled on-chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BudgetProfile {
+    entries: Vec<ProfileEntry>,
+    host_fn_stack: Vec<&'static str>,
+    // Budget counts at the time profiling was enabled, which the first
+    // recorded charge is measured from.
+    initial_cpu_insns: u64,
+    initial_mem_bytes: u64,
+    // Distinguishes this profile from the ones enabled earlier on the same
+    // budget, so that the host function guards created for an earlier profile
+    // don't touch the stack of this one.
+    generation: u64,
+}
+
+impl BudgetProfile {
+    pub(crate) fn record(
+        &mut self,
+        cost_type: CostType,
+        input: u64,
+        cpu_insns: u64,
+        mem_bytes: u64,
+    ) {
+        self.entries.push(ProfileEntry {
+            cost_type,
+            input,
+            host_fn: self.host_fn_stack.last().copied(),
+            cpu_insns,
+            mem_bytes,
+        })
+    }
+
+    pub fn entries(&self) -> &[ProfileEntry] {
+        &self.entries
+    }
+
+    /// Sums the cpu and memory cost of each charge, grouped by the host
+    /// function it was attributed to. Charges made outside any host function
+    /// are grouped under `None`. Results are sorted by descending cpu cost.
+    /// Only the budget consumed since profiling was enabled is accounted for.
+    pub fn totals_by_host_fn(&self) -> Vec<(Option<&'static str>, u64, u64)> {
+        let mut totals: Vec<(Option<&'static str>, u64, u64)> = Vec::new();
+        let (mut prev_cpu, mut prev_mem) = (self.initial_cpu_insns, self.initial_mem_bytes);
+        for e in self.entries.iter() {
+            let cpu = e.cpu_insns.saturating_sub(prev_cpu);
+            let mem = e.mem_bytes.saturating_sub(prev_mem);
+            prev_cpu = e.cpu_insns;
+            prev_mem = e.mem_bytes;
+            match totals.iter_mut().find(|(f, _, _)| *f == e.host_fn) {
+                Some((_, c, m)) => {
+                    *c = c.saturating_add(cpu);
+                    *m = m.saturating_add(mem);
+                }
+                None => totals.push((e.host_fn, cpu, mem)),
+            }
+        }
+        totals.sort_by_key(|t| Reverse(t.1));
+        totals
+    }
+
+    /// Renders the timeline as CSV, one row per charge, with a header row.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("cost_type,input,host_fn,cpu_insns,mem_bytes\n");
+        for e in self.entries.iter() {
+            // Writing to a String cannot fail.
+            let _ = writeln!(
+                out,
+                "{:?},{},{},{},{}",
+                e.cost_type,
+                e.input,
+                e.host_fn.unwrap_or(""),
+                e.cpu_insns,
+                e.mem_bytes
+            );
+        }
+        out
+    }
+
+    /// Renders the timeline as a JSON array of objects, one per charge.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        json::write_array(&mut out, &self.entries, |out, e| {
+            out.push_str("{\"cost_type\":");
+            json::write_string(out, &format!("{:?}", e.cost_type));
+            let _ = write!(out, ",\"input\":{},\"host_fn\":", e.input);
+            json::write_opt_string(out, e.host_fn);
+            let _ = write!(
+                out,
+                ",\"cpu_insns\":{},\"mem_bytes\":{}}}",
+                e.cpu_insns, e.mem_bytes
+            );
+        });
+        out
+    }
+}
+
+/// Guard returned by [`Budget::enter_host_fn`] that attributes charges to a
+/// host function for as long as it is alive. The guard is a no-op if it was
+/// created while profiling was off, and it only ever pops the stack of the
+/// profile it has pushed to, so toggling profiling while host functions are
+/// running doesn't misattribute the charges.
+#[cfg(any(test, feature = "vm"))]
+pub(crate) struct HostFnProfileGuard(Option<(Budget, u64)>);
+
+#[cfg(any(test, feature = "vm"))]
+impl Drop for HostFnProfileGuard {
+    fn drop(&mut self) {
+        if let Some((budget, generation)) = &self.0 {
+            if let Some(p) = budget.0.borrow_mut().profile.as_mut() {
+                if p.generation == *generation {
+                    p.host_fn_stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl Budget {
+    /// Starts recording every charge made against this budget, discarding
+    /// any previously recorded profile. Only the budget consumed from this
+    /// point on is attributed to host functions.
+    pub fn enable_profiling(&self) {
+        let mut b = self.0.borrow_mut();
+        b.profiles_enabled = b.profiles_enabled.saturating_add(1);
+        let profile = BudgetProfile {
+            initial_cpu_insns: b.cpu_insns.get_count(),
+            initial_mem_bytes: b.mem_bytes.get_count(),
+            generation: b.profiles_enabled,
+            ..Default::default()
+        };
+        b.profile = Some(profile)
+    }
+
+    /// Stops recording charges and returns the profile recorded so far, if
+    /// profiling was enabled.
+    pub fn disable_profiling(&self) -> Option<BudgetProfile> {
+        self.0.borrow_mut().profile.take()
+    }
+
+    pub fn is_profiling(&self) -> bool {
+        self.0.borrow().profile.is_some()
+    }
+
+    /// Returns a copy of the profile recorded so far, leaving profiling
+    /// enabled.
+    pub fn get_profile(&self) -> Option<BudgetProfile> {
+        self.0.borrow().profile.clone()
+    }
+
+    #[cfg(any(test, feature = "vm"))]
+    pub(crate) fn enter_host_fn(&self, name: &'static str) -> HostFnProfileGuard {
+        match self.0.borrow_mut().profile.as_mut() {
+            Some(p) => {
+                p.host_fn_stack.push(name);
+                HostFnProfileGuard(Some((self.clone(), p.generation)))
+            }
+            None => HostFnProfileGuard(None),
+        }
+    }
+}
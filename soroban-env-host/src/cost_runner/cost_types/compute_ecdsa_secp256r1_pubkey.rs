@@ -0,0 +1,17 @@
+use crate::{budget::CostType, cost_runner::CostRunner};
+
+pub struct ComputeEcdsaSecp256r1PubKeyRun;
+
+impl CostRunner for ComputeEcdsaSecp256r1PubKeyRun {
+    const COST_TYPE: CostType = CostType::ComputeEcdsaSecp256r1PubKey;
+    type SampleType = Vec<u8>;
+
+    fn run_iter(_host: &crate::Host, _iter: u64, sample: Self::SampleType) {
+        p256::ecdsa::VerifyingKey::from_sec1_bytes(sample.as_slice()).expect("publickey");
+    }
+
+    fn get_total_input(_host: &crate::Host, _sample: &Self::SampleType) -> u64 {
+        // input is ignored
+        Self::RUN_ITERATIONS
+    }
+}
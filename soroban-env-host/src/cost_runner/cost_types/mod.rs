@@ -1,5 +1,6 @@
 mod bytes_ops;
 mod charge_budget;
+mod compute_ecdsa_secp256r1_pubkey;
 mod compute_ed25519_pubkey;
 mod compute_sha256_hash;
 mod ed25519_scalar_mul;
@@ -16,6 +17,7 @@ mod scvec_to_host_vec;
 mod val_deser;
 mod val_ser;
 mod val_xdr_conv;
+mod verify_ecdsa_secp256r1_sig;
 mod verify_ed25519_sig;
 mod visit_object;
 #[cfg(feature = "vm")]
@@ -25,6 +27,7 @@ mod wasm_insn_exec;
 
 pub use bytes_ops::*;
 pub use charge_budget::*;
+pub use compute_ecdsa_secp256r1_pubkey::*;
 pub use compute_ed25519_pubkey::*;
 pub use compute_sha256_hash::*;
 pub use ed25519_scalar_mul::*;
@@ -41,6 +44,7 @@ pub use scvec_to_host_vec::*;
 pub use val_deser::*;
 pub use val_ser::*;
 pub use val_xdr_conv::*;
+pub use verify_ecdsa_secp256r1_sig::*;
 pub use verify_ed25519_sig::*;
 pub use visit_object::*;
 #[cfg(feature = "vm")]
@@ -0,0 +1,23 @@
+use crate::{budget::CostType, cost_runner::CostRunner};
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+pub struct VerifyEcdsaSecp256r1SigRun;
+
+#[derive(Clone)]
+pub struct VerifyEcdsaSecp256r1SigSample {
+    pub key: VerifyingKey,
+    pub msg: Vec<u8>,
+    pub sig: Signature,
+}
+
+impl CostRunner for VerifyEcdsaSecp256r1SigRun {
+    const COST_TYPE: CostType = CostType::VerifyEcdsaSecp256r1Sig;
+    type SampleType = VerifyEcdsaSecp256r1SigSample;
+
+    fn run_iter(_host: &crate::Host, _iter: u64, sample: Self::SampleType) {
+        sample
+            .key
+            .verify(sample.msg.as_slice(), &sample.sig)
+            .expect("verify");
+    }
+}
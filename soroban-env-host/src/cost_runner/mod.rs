@@ -1,5 +1,7 @@
 mod cost_types;
+mod replay;
 mod runner;
 
 pub use cost_types::*;
+pub use replay::{ChargeRecord, ChargeTrace, CostTypeDiff, ReplayTotals};
 pub use runner::CostRunner;
@@ -0,0 +1,147 @@
+use crate::{
+    budget::{Budget, BudgetProfile, CostType},
+    xdr::ScUnknownErrorCode,
+    HostError,
+};
+
+/// A single recorded `Budget::charge` call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChargeRecord {
+    pub cost_type: CostType,
+    pub input: u64,
+}
+
+/// The sequence of charges made during one run, which can be replayed
+/// against budgets with different cost models to quantify how a change to
+/// the models (or to the host itself, when the trace is saved from one host
+/// build and loaded in another) affects the cost of that run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChargeTrace(pub Vec<ChargeRecord>);
+
+impl ChargeTrace {
+    pub fn from_profile(profile: &BudgetProfile) -> Self {
+        ChargeTrace(
+            profile
+                .entries()
+                .iter()
+                .map(|e| ChargeRecord {
+                    cost_type: e.cost_type,
+                    input: e.input,
+                })
+                .collect(),
+        )
+    }
+
+    /// Serializes the trace as CSV with one `cost_type,input` row per charge.
+    /// Cost types are written by their numeric value, which is stable across
+    /// host builds, rather than their name.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("cost_type,input\n");
+        for r in self.0.iter() {
+            out.push_str(&format!("{},{}\n", r.cost_type as i32, r.input));
+        }
+        out
+    }
+
+    pub fn from_csv(csv: &str) -> Result<Self, HostError> {
+        let mut records = Vec::new();
+        for line in csv.lines().skip(1).filter(|l| !l.trim().is_empty()) {
+            let (ty, input) = line.split_once(',').ok_or(ScUnknownErrorCode::General)?;
+            let ty: i32 = ty.trim().parse().map_err(|_| ScUnknownErrorCode::General)?;
+            let cost_type = *CostType::variants()
+                .find(|ct| **ct as i32 == ty)
+                .ok_or(ScUnknownErrorCode::General)?;
+            let input: u64 = input
+                .trim()
+                .parse()
+                .map_err(|_| ScUnknownErrorCode::General)?;
+            records.push(ChargeRecord { cost_type, input });
+        }
+        Ok(ChargeTrace(records))
+    }
+
+    /// Evaluates every recorded charge against the cost models of `budget`.
+    /// The budget's counts and limits are not touched, so the replay can
+    /// never fail by running out of budget. The fixed per-charge overhead is
+    /// attributed to [`CostType::ChargeBudget`], as it is during execution.
+    pub fn replay(&self, budget: &Budget) -> ReplayTotals {
+        let n = CostType::variants().len();
+        let mut totals = ReplayTotals {
+            cpu_insns: vec![0; n],
+            mem_bytes: vec![0; n],
+        };
+        for r in self.0.iter() {
+            let (overhead, _) = budget.get_cost_models(CostType::ChargeBudget);
+            let (cpu, mem) = budget.get_cost_models(r.cost_type);
+            let i = CostType::ChargeBudget as usize;
+            totals.cpu_insns[i] = totals.cpu_insns[i].saturating_add(overhead.evaluate(1));
+            let i = r.cost_type as usize;
+            totals.cpu_insns[i] = totals.cpu_insns[i].saturating_add(cpu.evaluate(r.input));
+            totals.mem_bytes[i] = totals.mem_bytes[i].saturating_add(mem.evaluate(r.input));
+        }
+        totals
+    }
+}
+
+/// Per-[`CostType`] totals produced by [`ChargeTrace::replay`], indexed by
+/// cost type.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplayTotals {
+    pub cpu_insns: Vec<u64>,
+    pub mem_bytes: Vec<u64>,
+}
+
+impl ReplayTotals {
+    pub fn total_cpu_insns(&self) -> u64 {
+        self.cpu_insns.iter().fold(0, |a, b| a.saturating_add(*b))
+    }
+
+    pub fn total_mem_bytes(&self) -> u64 {
+        self.mem_bytes.iter().fold(0, |a, b| a.saturating_add(*b))
+    }
+
+    /// Compares these (base) totals with `new` totals and returns the cost
+    /// types whose cpu or memory cost changed, sorted by descending absolute
+    /// cpu change.
+    pub fn diff(&self, new: &ReplayTotals) -> Vec<CostTypeDiff> {
+        let get = |v: &Vec<u64>, i: usize| v.get(i).copied().unwrap_or(0);
+        let mut diffs: Vec<CostTypeDiff> = CostType::variants()
+            .map(|ct| {
+                let i = *ct as usize;
+                CostTypeDiff {
+                    cost_type: *ct,
+                    base_cpu_insns: get(&self.cpu_insns, i),
+                    new_cpu_insns: get(&new.cpu_insns, i),
+                    base_mem_bytes: get(&self.mem_bytes, i),
+                    new_mem_bytes: get(&new.mem_bytes, i),
+                }
+            })
+            .filter(|d| d.cpu_insns_delta() != 0 || d.mem_bytes_delta() != 0)
+            .collect();
+        diffs.sort_by(|a, b| {
+            b.cpu_insns_delta()
+                .unsigned_abs()
+                .cmp(&a.cpu_insns_delta().unsigned_abs())
+        });
+        diffs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostTypeDiff {
+    pub cost_type: CostType,
+    pub base_cpu_insns: u64,
+    pub new_cpu_insns: u64,
+    pub base_mem_bytes: u64,
+    pub new_mem_bytes: u64,
+}
+
+impl CostTypeDiff {
+    pub fn cpu_insns_delta(&self) -> i128 {
+        self.new_cpu_insns as i128 - self.base_cpu_insns as i128
+    }
+
+    pub fn mem_bytes_delta(&self) -> i128 {
+        self.new_mem_bytes as i128 - self.base_mem_bytes as i128
+    }
+}
@@ -0,0 +1,91 @@
+use super::LOG_EVENT_TOPIC;
+use crate::{
+    xdr::{self, Hash, ScObject, ScVal},
+    Host, HostError, Symbol, SymbolStr,
+};
+
+/// The level of a structured log record emitted by a contract via the `log`
+/// host function. The contracts pass the level as a `u32`, from `0` (`Trace`)
+/// to `4` (`Error`).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LogLevel {
+    #[default]
+    Trace = 0,
+    Debug = 1,
+    Info = 2,
+    Warn = 3,
+    Error = 4,
+}
+
+impl LogLevel {
+    pub fn from_u32(level: u32) -> Option<Self> {
+        match level {
+            0 => Some(LogLevel::Trace),
+            1 => Some(LogLevel::Debug),
+            2 => Some(LogLevel::Info),
+            3 => Some(LogLevel::Warn),
+            4 => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => log::Level::Trace,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Error => log::Level::Error,
+        }
+    }
+}
+
+/// A structured log record emitted by a contract via the `log` host function.
+/// The records below the level set with
+/// [Host::set_min_log_level](crate::Host::set_min_log_level) are not recorded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogEvent {
+    pub level: LogLevel,
+    pub contract_id: Option<Hash>,
+    pub target: Symbol,
+    pub values: Vec<ScVal>,
+}
+
+impl LogEvent {
+    // Converts the record into a `Diagnostic` contract event. The topics are
+    // the `log` symbol followed by the level and the target symbols, and the
+    // data is the `Vec` of the values.
+    // Metering: covered by components.
+    pub(crate) fn to_diagnostic(&self, host: &Host) -> Result<xdr::ContractEvent, HostError> {
+        let target: SymbolStr = self.target.into();
+        let target: &str = target.as_ref();
+        let topics = vec![
+            ScVal::Symbol(host.map_err(LOG_EVENT_TOPIC.try_into())?),
+            ScVal::Symbol(host.map_err(self.level.name().try_into())?),
+            ScVal::Symbol(host.map_err(target.try_into())?),
+        ];
+        Ok(xdr::ContractEvent {
+            ext: xdr::ExtensionPoint::V0,
+            contract_id: self.contract_id.clone(),
+            type_: xdr::ContractEventType::Diagnostic,
+            body: xdr::ContractEventBody::V0(xdr::ContractEventV0 {
+                topics: xdr::ScVec(host.map_err(topics.try_into())?),
+                data: ScVal::Object(Some(ScObject::Vec(xdr::ScVec(
+                    host.map_err(self.values.clone().try_into())?,
+                )))),
+            }),
+        })
+    }
+}
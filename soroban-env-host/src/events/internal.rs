@@ -1,9 +1,12 @@
-use super::{DebugEvent, Events, HostEvent};
+use super::{
+    DebugArg, DebugEvent, Event, Events, HostEvent, LogEvent, LogLevel, TraceEvent,
+    DIAGNOSTIC_EVENT_TOPIC,
+};
 use crate::{
     budget::{AsBudget, Budget},
     host::metered_clone::MeteredClone,
     xdr,
-    xdr::ScObject,
+    xdr::{Hash, ScObject, ScVal},
     Host, HostError, MeteredVector, Object, RawVal,
 };
 
@@ -15,6 +18,8 @@ pub(crate) struct InternalContractEvent {
     pub(crate) contract_id: Option<Object>,
     pub(crate) topics: Object,
     pub(crate) data: RawVal,
+    // Size of the topics and data serialized to XDR.
+    pub(crate) size: usize,
 }
 
 impl InternalContractEvent {
@@ -39,109 +44,363 @@ impl InternalContractEvent {
     }
 }
 
+/// The internal representation of a `DebugEvent` that is stored in the events
+/// buffer. When the XDR diagnostics are enabled, it also carries the
+/// `Diagnostic` contract event the debug event has been converted to at the
+/// time of recording (when all of its arguments are still alive).
+#[derive(Clone, Debug)]
+pub(crate) struct InternalDebugEvent {
+    pub(crate) event: DebugEvent,
+    pub(crate) diagnostic: Option<xdr::ContractEvent>,
+    // Cleared when the frame that has recorded the event is rolled back.
+    pub(crate) in_successful_contract_call: bool,
+}
+
+impl InternalDebugEvent {
+    // Converts the debug event into a `Diagnostic` contract event. The topics
+    // are the `diagnostic` symbol followed by the formatted message as
+    // `Bytes`, and the data is the `Vec` of the event arguments. Arguments
+    // that can't be converted to `ScVal` (or all the value arguments when
+    // `convert_vals` is false) are rendered as `Bytes` of their debug
+    // representation.
+    // Metering: covered by components.
+    pub(crate) fn to_diagnostic(
+        host: &Host,
+        contract_id: Option<Hash>,
+        event: &DebugEvent,
+        convert_vals: bool,
+    ) -> Result<xdr::ContractEvent, HostError> {
+        let topics = vec![
+            ScVal::Symbol(host.map_err(DIAGNOSTIC_EVENT_TOPIC.try_into())?),
+            Self::bytes_scval(host, event.to_string())?,
+        ];
+        let mut args = Vec::with_capacity(event.args.len());
+        for arg in event.args.iter() {
+            let val = match arg {
+                DebugArg::Val(rv) if convert_vals => host.from_host_val(*rv).ok(),
+                _ => None,
+            };
+            args.push(match val {
+                Some(val) => val,
+                None => Self::bytes_scval(host, arg.to_string())?,
+            });
+        }
+        Ok(xdr::ContractEvent {
+            ext: xdr::ExtensionPoint::V0,
+            contract_id,
+            type_: xdr::ContractEventType::Diagnostic,
+            body: xdr::ContractEventBody::V0(xdr::ContractEventV0 {
+                topics: xdr::ScVec(host.map_err(topics.try_into())?),
+                data: ScVal::Object(Some(ScObject::Vec(xdr::ScVec(
+                    host.map_err(args.try_into())?,
+                )))),
+            }),
+        })
+    }
+
+    fn bytes_scval(host: &Host, s: String) -> Result<ScVal, HostError> {
+        Ok(ScVal::Object(Some(ScObject::Bytes(
+            host.map_err(s.into_bytes().try_into())?,
+        ))))
+    }
+}
+
+/// The internal representation of a `LogEvent` that is stored in the events
+/// buffer. When the XDR diagnostics are enabled, it also carries the
+/// `Diagnostic` contract event the record has been converted to at the time of
+/// recording.
+#[derive(Clone, Debug)]
+pub(crate) struct InternalLogEvent {
+    pub(crate) event: LogEvent,
+    pub(crate) diagnostic: Option<xdr::ContractEvent>,
+    // Cleared when the frame that has recorded the event is rolled back.
+    pub(crate) in_successful_contract_call: bool,
+}
+
+/// The internal representation of a `TraceEvent` that is stored in the events
+/// buffer.
+#[derive(Clone, Debug)]
+pub(crate) struct InternalTraceEvent {
+    pub(crate) event: TraceEvent,
+    // Cleared when the frame that has recorded the event is rolled back.
+    pub(crate) in_successful_contract_call: bool,
+}
+
 /// The internal representation of an `Event` that is stored in the events buffer
 /// and designed to be cheap to cloned.
 #[derive(Clone, Debug, Default)]
 pub(crate) enum InternalEvent {
     Contract(InternalContractEvent),
-    Debug(DebugEvent),
+    Debug(InternalDebugEvent),
+    Trace(InternalTraceEvent),
+    Log(InternalLogEvent),
+    // A contract event emitted by a rolled back frame, already converted to
+    // XDR as the objects it refers to are gone.
+    RolledBackContract(xdr::ContractEvent),
     #[default]
     None,
 }
 
 impl MeteredClone for InternalEvent {}
 
-/// The events buffer. Stores `InternalEvent`s in the chronological order.
+impl InternalEvent {
+    // Converts the event into its external representation.
+    // Metering: covered by components.
+    pub(crate) fn to_host_event(&self, host: &Host) -> Result<HostEvent, HostError> {
+        let (event, in_successful_contract_call) = match self {
+            InternalEvent::Contract(c) => (Event::Contract(c.clone().to_xdr(host)?), true),
+            InternalEvent::Debug(d) => (
+                match &d.diagnostic {
+                    Some(ce) => Event::Diagnostic(ce.clone()),
+                    None => Event::Debug(d.event.clone()),
+                },
+                d.in_successful_contract_call,
+            ),
+            InternalEvent::Trace(t) => {
+                (Event::Trace(t.event.clone()), t.in_successful_contract_call)
+            }
+            InternalEvent::Log(l) => (
+                match &l.diagnostic {
+                    Some(ce) => Event::Diagnostic(ce.clone()),
+                    None => Event::Log(l.event.clone()),
+                },
+                l.in_successful_contract_call,
+            ),
+            InternalEvent::RolledBackContract(c) => (Event::Contract(c.clone()), false),
+            InternalEvent::None => return Err(host.err_general("Unexpected event type")),
+        };
+        Ok(HostEvent {
+            event,
+            in_successful_contract_call,
+        })
+    }
+
+    // Marks the event as emitted within a contract call that has failed.
+    fn mark_rolled_back(&mut self) {
+        match self {
+            InternalEvent::Debug(d) => d.in_successful_contract_call = false,
+            InternalEvent::Trace(t) => t.in_successful_contract_call = false,
+            InternalEvent::Log(l) => l.in_successful_contract_call = false,
+            InternalEvent::Contract(_)
+            | InternalEvent::RolledBackContract(_)
+            | InternalEvent::None => (),
+        }
+    }
+}
+
+/// The events buffer. The contract events are stored in a `MeteredVector`
+/// charged to the main budget, while the debug, trace and log events and the
+/// rolled back contract events are diagnostics, which are only ever charged to
+/// the shadow budget (as they are recorded), so they are stored apart in a
+/// plain `Vec`. Each diagnostic event is stored with the number of contract
+/// events recorded before it, which places it in the chronological order.
 #[derive(Clone, Default)]
 pub(crate) struct InternalEventsBuffer {
+    // Only holds `InternalEvent::Contract`s.
     pub(crate) vec: MeteredVector<InternalEvent>,
+    pub(crate) diagnostics: Vec<(usize, InternalEvent)>,
+    // Whether the debug events should be converted to the XDR `Diagnostic`
+    // contract events when they are recorded.
+    pub(crate) xdr_diagnostics: bool,
+    // Whether the frame pushes and pops should be recorded as `TraceEvent`s.
+    pub(crate) call_trace: bool,
+    // Contract log records below this level are not recorded.
+    pub(crate) min_log_level: LogLevel,
+    // Number and total size of the contract events recorded (and not rolled
+    // back) during the current invocation.
+    pub(crate) invocation_events_count: usize,
+    pub(crate) invocation_events_size: usize,
+    // Number of events at the start of the buffer (in the chronological
+    // order) that have been handed to the event sink.
+    pub(crate) sink_delivered: usize,
 }
 
 impl InternalEventsBuffer {
     // Records an InternalEvent
-    // Metering covered by the `MeteredVec`.
+    // Metering: the contract events are covered by the `MeteredVec`, the
+    // other events are charged to the shadow budget by the callers.
     pub fn record(&mut self, e: InternalEvent, budget: &Budget) -> Result<(), HostError> {
-        self.vec = self.vec.push_back(e, budget)?;
+        match e {
+            InternalEvent::Contract(c) => {
+                self.invocation_events_count += 1;
+                self.invocation_events_size += c.size;
+                self.vec = self.vec.push_back(InternalEvent::Contract(c), budget)?;
+            }
+            e => self.diagnostics.push((self.vec.len(), e)),
+        }
         Ok(())
     }
 
-    fn defunct_contract_event(c: &mut InternalContractEvent) -> InternalEvent {
+    // Number of events in the buffer.
+    pub(crate) fn len(&self) -> usize {
+        self.vec.len() + self.diagnostics.len()
+    }
+
+    // Splits the first `events` events (in the chronological order) into the
+    // number of contract events and the number of diagnostic events among
+    // them. The `j`-th diagnostic event is preceded by `pos + j` events.
+    fn split_point(&self, events: usize) -> (usize, usize) {
+        let diagnostics = self
+            .diagnostics
+            .iter()
+            .enumerate()
+            .take_while(|(j, (pos, _))| pos + j < events)
+            .count();
+        (events.saturating_sub(diagnostics), diagnostics)
+    }
+
+    // Returns the events in the chronological order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &InternalEvent> {
+        let mut events = Vec::with_capacity(self.len());
+        let mut contract_events = self.vec.iter();
+        let mut merged = 0;
+        for (pos, e) in self.diagnostics.iter() {
+            events.extend(contract_events.by_ref().take(pos.saturating_sub(merged)));
+            merged = merged.max(*pos);
+            events.push(e);
+        }
+        events.extend(contract_events);
+        events.into_iter()
+    }
+
+    // Resets the per-invocation usage once the invocation is finished.
+    pub(crate) fn reset_invocation_usage(&mut self) {
+        self.invocation_events_count = 0;
+        self.invocation_events_size = 0;
+    }
+
+    // Returns the events that haven't been handed to the event sink yet.
+    pub(crate) fn undelivered_events(&self) -> Vec<InternalEvent> {
+        self.iter().skip(self.sink_delivered).cloned().collect()
+    }
+
+    // Builds a debug event recorded by the buffer itself. Its arguments are
+    // never converted to `ScVal`s, as the conversion may need to record
+    // events of its own (and may refer to the objects being rolled back).
+    fn internal_debug_event(
+        host: &Host,
+        event: DebugEvent,
+        xdr_diagnostics: bool,
+        in_successful_contract_call: bool,
+    ) -> Result<InternalEvent, HostError> {
+        let diagnostic = if xdr_diagnostics {
+            Some(InternalDebugEvent::to_diagnostic(
+                host, None, &event, false,
+            )?)
+        } else {
+            None
+        };
+        Ok(InternalEvent::Debug(InternalDebugEvent {
+            event,
+            diagnostic,
+            in_successful_contract_call,
+        }))
+    }
+
+    fn defunct_contract_event(c: &InternalContractEvent) -> DebugEvent {
         let ty: RawVal = <i32>::from(c.type_).into();
         let id: RawVal = c.contract_id.map_or(RawVal::from_void(), |obj| obj.into());
-        let dbg = DebugEvent::new()
+        DebugEvent::new()
             .msg("rolled-back contract event: type {}, id {}, topics {}, data {}")
             .arg(ty)
             .arg(id)
             .arg(RawVal::from(c.topics))
-            .arg(c.data);
-        InternalEvent::Debug(dbg)
-    }
-
-    /// Rolls back the event buffer starting at `events`. Any `ContractEvent` will be converted
-    /// to a `DebugEvent` indicating the event has been rolled back. An additional `DebugEvent`
-    /// will be pushed at the end indicating the rollback happened.
-    // Metering covered by the `MeteredVec`
-    pub fn rollback(&mut self, events: usize, host: &Host) -> Result<(), HostError> {
-        let mut rollback_count = 0u32;
-        self.vec = self.vec.retain_mut(
-            |i, e| {
-                if i < events {
-                    Ok(true)
-                } else {
-                    if let InternalEvent::Contract(c) = e {
-                        *e = Self::defunct_contract_event(c);
-                        rollback_count += 1;
-                        Ok(true)
-                    } else {
-                        Ok(true)
+            .arg(c.data)
+    }
+
+    // Returns the contract events starting at `events`, in order.
+    pub(crate) fn contract_events_since(&self, events: usize) -> Vec<InternalContractEvent> {
+        let (contract_events, _) = self.split_point(events);
+        self.vec
+            .iter()
+            .skip(contract_events)
+            .filter_map(|e| match e {
+                InternalEvent::Contract(c) => Some(c.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Rolls back the event buffer starting at `events`. The contract events
+    /// that have their XDR representation in `xdr` (in the order returned by
+    /// [`InternalEventsBuffer::contract_events_since`]) are kept as rolled
+    /// back contract events, and the others are converted to `DebugEvent`s
+    /// indicating the event has been rolled back. Any other event will be
+    /// marked as not being in a successful contract call. An additional
+    /// `DebugEvent` will be pushed at the end indicating the rollback happened.
+    // Metering: the contract events are covered by the `MeteredVec`, the
+    // diagnostic events are not charged.
+    pub fn rollback(
+        &mut self,
+        events: usize,
+        xdr: Vec<Option<xdr::ContractEvent>>,
+        host: &Host,
+    ) -> Result<(), HostError> {
+        let (contract_start, diagnostics_start) = self.split_point(events);
+        let xdr_diagnostics = self.xdr_diagnostics;
+        let mut xdr = xdr.into_iter();
+        let mut rolled_back = Vec::new();
+        let mut rollback_size = 0usize;
+        for e in self.vec.iter().skip(contract_start) {
+            if let InternalEvent::Contract(c) = e {
+                rollback_size += c.size;
+                rolled_back.push(match xdr.next().flatten() {
+                    Some(ce) => InternalEvent::RolledBackContract(ce),
+                    None => {
+                        let dbg = Self::defunct_contract_event(c);
+                        Self::internal_debug_event(host, dbg, xdr_diagnostics, false)?
                     }
+                });
+            }
+        }
+        let rollback_count = rolled_back.len();
+        if rollback_count > 0 {
+            self.vec = self.vec.slice(0..contract_start, host.as_budget())?;
+        }
+        // The diagnostic events recorded since the rollback point are marked
+        // as not being in a successful contract call, and the rolled back
+        // contract events are moved in among them, each one before the
+        // diagnostic events that have been recorded after it.
+        let mut rolled_back = rolled_back.into_iter();
+        let mut next_contract_pos = contract_start;
+        for (pos, mut e) in self.diagnostics.split_off(diagnostics_start) {
+            while next_contract_pos < pos {
+                match rolled_back.next() {
+                    Some(rb) => self.diagnostics.push((contract_start, rb)),
+                    None => break,
                 }
-            },
-            host.as_budget(),
-        )?;
+                next_contract_pos += 1;
+            }
+            e.mark_rolled_back();
+            self.diagnostics.push((contract_start, e));
+        }
+        self.diagnostics
+            .extend(rolled_back.map(|rb| (contract_start, rb)));
+        self.invocation_events_count = self.invocation_events_count.saturating_sub(rollback_count);
+        self.invocation_events_size = self.invocation_events_size.saturating_sub(rollback_size);
         // If any events were rolled back, we push one more debug event at the end to
         // let the user know.
         if rollback_count > 0 {
-            self.vec = self.vec.push_back(
-                InternalEvent::Debug(
-                    DebugEvent::new()
-                        .msg("{} contract events rolled back. Rollback start pos = {}")
-                        .arg(RawVal::from(rollback_count))
-                        .arg(host.usize_to_rawval_u32(events)?),
-                ),
-                host.as_budget(),
+            let summary = Self::internal_debug_event(
+                host,
+                DebugEvent::new()
+                    .msg("{} contract events rolled back. Rollback start pos = {}")
+                    .arg(host.usize_to_rawval_u32(rollback_count)?)
+                    .arg(host.usize_to_rawval_u32(events)?),
+                xdr_diagnostics,
+                true,
             )?;
+            self.diagnostics.push((self.vec.len(), summary));
         }
         Ok(())
     }
 
-    #[allow(unused)]
-    pub fn dump_to_debug_log(&self) {
-        use log::debug;
-        debug!("=======Start of events=======");
-        for e in self.vec.iter() {
-            match e {
-                InternalEvent::Contract(c) => debug!("Contract event: {:?}", c),
-                InternalEvent::Debug(d) => debug!("Debug event: {}", d),
-                InternalEvent::None => (),
-            }
-        }
-        debug!("========End of events========")
-    }
-
     /// Converts the internal events into their external representation. This should only be called
     /// either when the host is finished (via `try_finish`), or when an error occurs.
     // Metering: the new vec allocation is not charged, but that should be fine.
     pub fn externalize(&self, host: &Host) -> Result<Events, HostError> {
-        let vec: Result<Vec<HostEvent>, HostError> = self
-            .vec
-            .iter()
-            .map(|e| match e {
-                InternalEvent::Contract(c) => Ok(HostEvent::Contract(c.clone().to_xdr(host)?)),
-                InternalEvent::Debug(d) => Ok(HostEvent::Debug(d.clone())),
-                InternalEvent::None => Err(host.err_general("Unexpected event type")),
-            })
-            .collect();
+        let vec: Result<Vec<HostEvent>, HostError> =
+            self.iter().map(|e| e.to_host_event(host)).collect();
         Ok(Events(vec?))
     }
 }
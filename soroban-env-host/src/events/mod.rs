@@ -1,15 +1,58 @@
+mod contract_log;
 mod debug;
 mod internal;
+mod query;
+mod render;
+mod sink;
+mod spec;
+mod trace;
 
+pub use contract_log::{LogEvent, LogLevel};
 pub use debug::{DebugArg, DebugError, DebugEvent};
-pub(crate) use internal::{InternalContractEvent, InternalEvent, InternalEventsBuffer};
+pub(crate) use internal::{
+    InternalContractEvent, InternalDebugEvent, InternalEvent, InternalEventsBuffer,
+    InternalLogEvent, InternalTraceEvent,
+};
+pub use query::{EventsQuery, TokenEvent};
+pub use render::{address_to_strkey, contract_id_to_strkey, PrettyContractEvent, PrettyScVal};
+pub use sink::{EventSink, EventSinkMode};
+pub use spec::{
+    contract_event_specs_to_json, token_event_specs, ContractEventSpec, EventParamSpec,
+    EventValueType,
+};
+pub use trace::TraceEvent;
+
+use crate::{
+    xdr::{ScHostContextErrorCode, ScHostFnErrorCode, ScHostObjErrorCode, ScHostValErrorCode},
+    Status,
+};
 
 /// The external representation of a host event.
 // TODO: optimize storage on this to use pools / bumpalo / etc.
 #[derive(Clone, Debug)]
-pub enum HostEvent {
+pub struct HostEvent {
+    pub event: Event,
+    /// Whether the event has been emitted within a contract call that has
+    /// succeeded. The events emitted within the failed calls (e.g. the ones
+    /// that have been handled by `try_call`) are rolled back, but are still
+    /// kept in order to explain the failure. Only the contract events emitted
+    /// within the successful calls make it to the ledger.
+    pub in_successful_contract_call: bool,
+}
+
+/// The kinds of host events along with their contents.
+#[derive(Clone, Debug)]
+pub enum Event {
     Contract(crate::xdr::ContractEvent),
     Debug(DebugEvent),
+    /// A debug event or a contract log record converted to the XDR
+    /// `Diagnostic` contract event (see
+    /// [Host::enable_xdr_diagnostic_events](crate::Host::enable_xdr_diagnostic_events)).
+    Diagnostic(crate::xdr::ContractEvent),
+    Trace(TraceEvent),
+    /// A structured log record emitted by a contract, unless it has been
+    /// converted to a `Diagnostic` contract event.
+    Log(LogEvent),
 }
 
 /// The external representation of events in the chronological order.
@@ -21,3 +64,139 @@ pub struct Events(pub Vec<HostEvent>);
 pub(crate) const CONTRACT_EVENT_TOPICS_LIMIT: usize = 4;
 // Maximum number of bytes in a topic `Bytes`.
 pub(crate) const TOPIC_BYTES_LENGTH_LIMIT: usize = 32;
+// Maximum number of elements in a `Vec` or `Map` within the topics or data.
+pub(crate) const CONTAINER_LENGTH_LIMIT: usize = 64;
+// Maximum nesting depth of the containers within a topic or the data.
+pub(crate) const CONTAINER_DEPTH_LIMIT: u32 = 8;
+// Maximum size of all the topics of an event, serialized to XDR.
+pub(crate) const TOPICS_SIZE_LIMIT: usize = 1024;
+// Maximum size of the data of an event, serialized to XDR.
+pub(crate) const DATA_SIZE_LIMIT: usize = 16 * 1024;
+// Maximum number of contract events emitted by a single invocation.
+pub(crate) const INVOCATION_EVENTS_COUNT_LIMIT: usize = 100;
+// Maximum total size of the contract events emitted by a single invocation.
+pub(crate) const INVOCATION_EVENTS_SIZE_LIMIT: usize = 64 * 1024;
+
+/// Limits on the shape and size of the contract events, enforced every time a
+/// contract event is recorded. The violations are described in
+/// [`ContractEventLimitViolation`]. The limits are set with
+/// [`Host::set_contract_event_limits`](crate::Host::set_contract_event_limits).
+///
+/// The per-invocation limits apply to the events emitted since the last time
+/// the host context has been empty and don't count the rolled back events. The
+/// size of an event is the size of its topics and data serialized to XDR.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractEventLimits {
+    pub max_topics: usize,
+    pub max_topic_bytes_length: usize,
+    pub max_container_length: usize,
+    pub max_container_depth: u32,
+    pub max_topics_size: usize,
+    pub max_data_size: usize,
+    pub max_invocation_events_count: usize,
+    pub max_invocation_events_size: usize,
+}
+
+impl Default for ContractEventLimits {
+    fn default() -> Self {
+        Self {
+            max_topics: CONTRACT_EVENT_TOPICS_LIMIT,
+            max_topic_bytes_length: TOPIC_BYTES_LENGTH_LIMIT,
+            max_container_length: CONTAINER_LENGTH_LIMIT,
+            max_container_depth: CONTAINER_DEPTH_LIMIT,
+            max_topics_size: TOPICS_SIZE_LIMIT,
+            max_data_size: DATA_SIZE_LIMIT,
+            max_invocation_events_count: INVOCATION_EVENTS_COUNT_LIMIT,
+            max_invocation_events_size: INVOCATION_EVENTS_SIZE_LIMIT,
+        }
+    }
+}
+
+/// Kinds of invalid contract events. Each of them is reported with its own
+/// status (see [`ContractEventLimitViolation::status`]) and a debug event
+/// naming the violation (see [`ContractEventLimitViolation::name`]) and the
+/// limit that has been exceeded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContractEventLimitViolation {
+    /// More topics than [`ContractEventLimits::max_topics`].
+    TooManyTopics,
+    /// A topic is (or contains) a value that is not allowed in topics, such as
+    /// the contract code.
+    TopicTypeNotAllowed,
+    /// A topic `Bytes` longer than [`ContractEventLimits::max_topic_bytes_length`].
+    TopicBytesTooLong,
+    /// A `Vec` or a `Map` with more elements than
+    /// [`ContractEventLimits::max_container_length`].
+    ContainerTooLong,
+    /// Containers nested deeper than [`ContractEventLimits::max_container_depth`].
+    ContainerTooDeep,
+    /// Topics serialized to more than [`ContractEventLimits::max_topics_size`]
+    /// bytes.
+    TopicsTooLarge,
+    /// Data serialized to more than [`ContractEventLimits::max_data_size`]
+    /// bytes.
+    DataTooLarge,
+    /// More events emitted by an invocation than
+    /// [`ContractEventLimits::max_invocation_events_count`].
+    TooManyInvocationEvents,
+    /// Events emitted by an invocation larger in total than
+    /// [`ContractEventLimits::max_invocation_events_size`].
+    InvocationEventsTooLarge,
+}
+
+impl ContractEventLimitViolation {
+    /// The status the violation is reported with. The XDR doesn't define
+    /// event-specific status codes, so every violation is mapped to a distinct
+    /// existing status closest to it. This keeps the kind of the violation
+    /// known from the status alone, when the debug events are not recorded.
+    pub fn status(&self) -> Status {
+        match self {
+            ContractEventLimitViolation::TooManyTopics => {
+                ScHostFnErrorCode::InputArgsWrongLength.into()
+            }
+            ContractEventLimitViolation::TopicTypeNotAllowed => {
+                ScHostFnErrorCode::InputArgsWrongType.into()
+            }
+            ContractEventLimitViolation::TopicBytesTooLong => {
+                ScHostFnErrorCode::InputArgsInvalid.into()
+            }
+            ContractEventLimitViolation::ContainerTooLong => {
+                ScHostObjErrorCode::VecIndexOutOfBound.into()
+            }
+            ContractEventLimitViolation::ContainerTooDeep => {
+                ScHostObjErrorCode::UnexpectedType.into()
+            }
+            ContractEventLimitViolation::TopicsTooLarge => ScHostValErrorCode::U32OutOfRange.into(),
+            ContractEventLimitViolation::DataTooLarge => ScHostValErrorCode::U63OutOfRange.into(),
+            ContractEventLimitViolation::TooManyInvocationEvents => {
+                ScHostFnErrorCode::UnexpectedHostFunctionAction.into()
+            }
+            ContractEventLimitViolation::InvocationEventsTooLarge => {
+                ScHostContextErrorCode::UnknownError.into()
+            }
+        }
+    }
+
+    /// The name of the violation, which is the first argument of the debug
+    /// event the violation is reported with.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContractEventLimitViolation::TooManyTopics => "TooManyTopics",
+            ContractEventLimitViolation::TopicTypeNotAllowed => "TopicTypeNotAllowed",
+            ContractEventLimitViolation::TopicBytesTooLong => "TopicBytesTooLong",
+            ContractEventLimitViolation::ContainerTooLong => "ContainerTooLong",
+            ContractEventLimitViolation::ContainerTooDeep => "ContainerTooDeep",
+            ContractEventLimitViolation::TopicsTooLarge => "TopicsTooLarge",
+            ContractEventLimitViolation::DataTooLarge => "DataTooLarge",
+            ContractEventLimitViolation::TooManyInvocationEvents => "TooManyInvocationEvents",
+            ContractEventLimitViolation::InvocationEventsTooLarge => "InvocationEventsTooLarge",
+        }
+    }
+}
+
+// First topic of the `Diagnostic` contract events produced from the debug
+// events.
+pub(crate) const DIAGNOSTIC_EVENT_TOPIC: &str = "diagnostic";
+// First topic of the `Diagnostic` contract events produced from the contract
+// log records.
+pub(crate) const LOG_EVENT_TOPIC: &str = "log";
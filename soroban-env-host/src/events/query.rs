@@ -0,0 +1,236 @@
+use super::{token_event_specs, Event, Events};
+use crate::xdr::{
+    ContractEvent, ContractEventBody, ContractEventType, Hash, Int128Parts, ScAddress, ScObject,
+    ScStatic, ScVal,
+};
+
+/// A filter over the contract events stored in [`Events`], see
+/// [`Events::query`].
+///
+/// By default only the events that haven't been rolled back are matched.
+#[derive(Clone)]
+pub struct EventsQuery<'a> {
+    events: &'a Events,
+    type_: Option<ContractEventType>,
+    contract_id: Option<Hash>,
+    topic_prefix: Vec<ScVal>,
+    rolled_back: Option<bool>,
+}
+
+impl Events {
+    /// Starts a query over the contract events (including the diagnostic
+    /// ones) in the chronological order. The events emitted within the failed
+    /// contract calls are considered rolled back.
+    pub fn query(&self) -> EventsQuery<'_> {
+        EventsQuery {
+            events: self,
+            type_: None,
+            contract_id: None,
+            topic_prefix: vec![],
+            rolled_back: Some(false),
+        }
+    }
+}
+
+impl<'a> EventsQuery<'a> {
+    /// Matches only the events of the given type.
+    pub fn with_type(mut self, type_: ContractEventType) -> Self {
+        self.type_ = Some(type_);
+        self
+    }
+
+    /// Matches only the events emitted by the given contract.
+    pub fn with_contract_id(mut self, contract_id: Hash) -> Self {
+        self.contract_id = Some(contract_id);
+        self
+    }
+
+    /// Matches only the events whose topics start with the given values.
+    pub fn with_topic_prefix(mut self, topic_prefix: Vec<ScVal>) -> Self {
+        self.topic_prefix = topic_prefix;
+        self
+    }
+
+    /// Matches the events regardless of whether they have been rolled back.
+    pub fn include_rolled_back(mut self) -> Self {
+        self.rolled_back = None;
+        self
+    }
+
+    /// Matches only the events that have been rolled back.
+    pub fn rolled_back_only(mut self) -> Self {
+        self.rolled_back = Some(true);
+        self
+    }
+
+    /// Returns the matching events along with whether they have been rolled
+    /// back.
+    pub fn iter_with_status(&self) -> impl Iterator<Item = (&'a ContractEvent, bool)> + '_ {
+        self.events
+            .0
+            .iter()
+            .filter_map(|e| match &e.event {
+                Event::Contract(ce) | Event::Diagnostic(ce) => {
+                    Some((ce, !e.in_successful_contract_call))
+                }
+                Event::Debug(_) | Event::Trace(_) | Event::Log(_) => None,
+            })
+            .filter(move |(ce, rolled_back)| self.matches(ce, *rolled_back))
+    }
+
+    /// Returns the matching events.
+    pub fn iter(&self) -> impl Iterator<Item = &'a ContractEvent> + '_ {
+        self.iter_with_status().map(|(ce, _)| ce)
+    }
+
+    /// Decodes the matching events emitted by the native token contract,
+    /// skipping the events that don't have the token event format.
+    pub fn token_events(&self) -> impl Iterator<Item = TokenEvent> + '_ {
+        self.iter().filter_map(TokenEvent::from_xdr)
+    }
+
+    fn matches(&self, ce: &ContractEvent, rolled_back: bool) -> bool {
+        if let Some(expected) = self.rolled_back {
+            if expected != rolled_back {
+                return false;
+            }
+        }
+        if let Some(type_) = self.type_ {
+            if ce.type_ != type_ {
+                return false;
+            }
+        }
+        if let Some(contract_id) = &self.contract_id {
+            if ce.contract_id.as_ref() != Some(contract_id) {
+                return false;
+            }
+        }
+        let ContractEventBody::V0(body) = &ce.body;
+        body.topics.0.as_slice().starts_with(&self.topic_prefix)
+    }
+}
+
+/// An event emitted by the native token contract.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TokenEvent {
+    IncrAllow {
+        from: ScAddress,
+        to: ScAddress,
+        amount: i128,
+    },
+    DecrAllow {
+        from: ScAddress,
+        to: ScAddress,
+        amount: i128,
+    },
+    Transfer {
+        from: ScAddress,
+        to: ScAddress,
+        amount: i128,
+    },
+    Mint {
+        admin: ScAddress,
+        to: ScAddress,
+        amount: i128,
+    },
+    Clawback {
+        admin: ScAddress,
+        from: ScAddress,
+        amount: i128,
+    },
+    SetAuth {
+        admin: ScAddress,
+        id: ScAddress,
+        authorize: bool,
+    },
+    SetAdmin {
+        admin: ScAddress,
+        new_admin: ScAddress,
+    },
+    Burn {
+        from: ScAddress,
+        amount: i128,
+    },
+}
+
+impl TokenEvent {
+    /// Decodes the event from its topics and data with the token event specs
+    /// (see [`token_event_specs`]), returns `None` if the event doesn't have
+    /// the format of any token event.
+    pub fn from_xdr(event: &ContractEvent) -> Option<Self> {
+        if event.type_ != ContractEventType::Contract {
+            return None;
+        }
+        let (spec, params) = token_event_specs()
+            .iter()
+            .find_map(|spec| Some((spec, spec.decode(event)?)))?;
+        let param = |name: &str| {
+            params
+                .iter()
+                .find_map(|(n, val)| (*n == name).then_some(val))
+        };
+        let address = |name: &str| scval_to_address(param(name)?);
+        let amount = |name: &str| scval_to_i128(param(name)?);
+        Some(match spec.name {
+            "incr_allow" => TokenEvent::IncrAllow {
+                from: address("from")?,
+                to: address("to")?,
+                amount: amount("amount")?,
+            },
+            "decr_allow" => TokenEvent::DecrAllow {
+                from: address("from")?,
+                to: address("to")?,
+                amount: amount("amount")?,
+            },
+            "transfer" => TokenEvent::Transfer {
+                from: address("from")?,
+                to: address("to")?,
+                amount: amount("amount")?,
+            },
+            "mint" => TokenEvent::Mint {
+                admin: address("admin")?,
+                to: address("to")?,
+                amount: amount("amount")?,
+            },
+            "clawback" => TokenEvent::Clawback {
+                admin: address("admin")?,
+                from: address("from")?,
+                amount: amount("amount")?,
+            },
+            "set_auth" => TokenEvent::SetAuth {
+                admin: address("admin")?,
+                id: address("id")?,
+                authorize: match param("authorize")? {
+                    ScVal::Static(ScStatic::True) => true,
+                    ScVal::Static(ScStatic::False) => false,
+                    _ => return None,
+                },
+            },
+            "set_admin" => TokenEvent::SetAdmin {
+                admin: address("admin")?,
+                new_admin: address("new_admin")?,
+            },
+            "burn" => TokenEvent::Burn {
+                from: address("from")?,
+                amount: amount("amount")?,
+            },
+            _ => return None,
+        })
+    }
+}
+
+fn scval_to_address(val: &ScVal) -> Option<ScAddress> {
+    match val {
+        ScVal::Object(Some(ScObject::Address(address))) => Some(address.clone()),
+        _ => None,
+    }
+}
+
+fn scval_to_i128(val: &ScVal) -> Option<i128> {
+    match val {
+        ScVal::Object(Some(ScObject::I128(Int128Parts { lo, hi }))) => {
+            Some((((*hi as u128) << 64) | (*lo as u128)) as i128)
+        }
+        _ => None,
+    }
+}
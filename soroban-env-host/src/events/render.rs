@@ -0,0 +1,293 @@
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use super::{DebugArg, DebugEvent, Event, HostEvent, LogEvent, TraceEvent};
+use crate::{
+    xdr::{
+        AccountId, ContractEvent, ContractEventBody, Hash, Int128Parts, PublicKey, ScAddress,
+        ScContractCode, ScObject, ScStatic, ScVal, Uint256,
+    },
+    Host, RawVal, Status, Symbol, SymbolStr,
+};
+
+// Strkey version bytes of the ed25519 public keys (`G...`) and the contract
+// ids (`C...`).
+const STRKEY_VERSION_ACCOUNT_ID: u8 = 6 << 3;
+const STRKEY_VERSION_CONTRACT: u8 = 2 << 3;
+
+// CRC16-XModem checksum of a strkey payload.
+fn strkey_checksum(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+// Unpadded RFC 4648 base32.
+fn base32(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut out = String::with_capacity((data.len() * 8 + 4) / 5);
+    let mut buf: u32 = 0;
+    let mut bits = 0;
+    for byte in data {
+        buf = (buf << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((buf >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[((buf << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+fn strkey(version: u8, key: &[u8; 32]) -> String {
+    let mut data = Vec::with_capacity(35);
+    data.push(version);
+    data.extend_from_slice(key);
+    let checksum = strkey_checksum(&data);
+    data.extend_from_slice(&checksum.to_le_bytes());
+    base32(&data)
+}
+
+/// Returns the strkey of a contract id (`C...`).
+pub fn contract_id_to_strkey(id: &Hash) -> String {
+    strkey(STRKEY_VERSION_CONTRACT, &id.0)
+}
+
+/// Returns the strkey of an address: `G...` for the accounts and `C...` for
+/// the contracts.
+pub fn address_to_strkey(address: &ScAddress) -> String {
+    match address {
+        ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(key)))) => {
+            strkey(STRKEY_VERSION_ACCOUNT_ID, key)
+        }
+        ScAddress::Contract(id) => contract_id_to_strkey(id),
+    }
+}
+
+fn u128_from_parts(parts: &Int128Parts) -> u128 {
+    parts.lo as u128 | ((parts.hi as u128) << 64)
+}
+
+fn fmt_list<'a, T: 'a>(
+    f: &mut Formatter<'_>,
+    items: impl IntoIterator<Item = &'a T>,
+    fmt_item: impl Fn(&mut Formatter<'_>, &T) -> FmtResult,
+) -> FmtResult {
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        fmt_item(f, item)?;
+    }
+    Ok(())
+}
+
+/// Renders an [`ScVal`] in a human-readable form: the containers are shown as
+/// the nested `[...]` (vectors) and `{key: value}` (maps), the addresses as
+/// strkeys, the integers in decimal and the bytes in hex.
+pub struct PrettyScVal<'a>(pub &'a ScVal);
+
+impl Display for PrettyScVal<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.0 {
+            ScVal::U63(u) => write!(f, "U63({})", u),
+            ScVal::U32(u) => write!(f, "U32({})", u),
+            ScVal::I32(i) => write!(f, "I32({})", i),
+            ScVal::Static(ScStatic::Void) => write!(f, "()"),
+            ScVal::Static(ScStatic::True) => write!(f, "true"),
+            ScVal::Static(ScStatic::False) => write!(f, "false"),
+            ScVal::Static(other) => write!(f, "{}", other.name()),
+            ScVal::Object(None) => write!(f, "Object(None)"),
+            ScVal::Object(Some(obj)) => PrettyScObject(obj).fmt(f),
+            ScVal::Symbol(s) => write!(f, "Symbol({})", String::from_utf8_lossy(s.as_slice())),
+            ScVal::Bitset(b) => write!(f, "Bitset({:#b})", b),
+            ScVal::Status(st) => write!(f, "{:?}", Status::from(st.clone())),
+        }
+    }
+}
+
+struct PrettyScObject<'a>(&'a ScObject);
+
+impl Display for PrettyScObject<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.0 {
+            ScObject::Vec(v) => {
+                write!(f, "[")?;
+                fmt_list(f, v.iter(), |f, e| PrettyScVal(e).fmt(f))?;
+                write!(f, "]")
+            }
+            ScObject::Map(m) => {
+                write!(f, "{{")?;
+                fmt_list(f, m.iter(), |f, e| {
+                    write!(f, "{}: {}", PrettyScVal(&e.key), PrettyScVal(&e.val))
+                })?;
+                write!(f, "}}")
+            }
+            ScObject::U64(u) => write!(f, "U64({})", u),
+            ScObject::I64(i) => write!(f, "I64({})", i),
+            ScObject::U128(parts) => write!(f, "U128({})", u128_from_parts(parts)),
+            ScObject::I128(parts) => write!(f, "I128({})", u128_from_parts(parts) as i128),
+            ScObject::Bytes(b) => write!(f, "Bytes({})", hex::encode(b.as_vec())),
+            ScObject::ContractCode(ScContractCode::WasmRef(hash)) => {
+                write!(f, "WasmRef({})", hex::encode(hash.0))
+            }
+            ScObject::ContractCode(ScContractCode::Token) => write!(f, "Token"),
+            ScObject::Address(address) => write!(f, "{}", address_to_strkey(address)),
+            ScObject::NonceKey(address) => write!(f, "NonceKey({})", address_to_strkey(address)),
+        }
+    }
+}
+
+/// Renders a [`ContractEvent`] as its type, the strkey of the contract that
+/// has emitted it, and its topics and data rendered with [`PrettyScVal`].
+pub struct PrettyContractEvent<'a>(pub &'a ContractEvent);
+
+impl Display for PrettyContractEvent<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} event", self.0.type_.name())?;
+        if let Some(id) = &self.0.contract_id {
+            write!(f, " from {}", contract_id_to_strkey(id))?;
+        }
+        let ContractEventBody::V0(body) = &self.0.body;
+        write!(f, ": topics [")?;
+        fmt_list(f, body.topics.iter(), |f, t| PrettyScVal(t).fmt(f))?;
+        write!(f, "], data {}", PrettyScVal(&body.data))
+    }
+}
+
+fn fmt_function(
+    f: &mut Formatter<'_>,
+    contract_id: &Option<Hash>,
+    function: &Option<Symbol>,
+) -> FmtResult {
+    match (contract_id, function) {
+        (Some(id), Some(func)) => {
+            let func: SymbolStr = func.into();
+            let func: &str = func.as_ref();
+            write!(f, "{}.{}", contract_id_to_strkey(id), func)
+        }
+        _ => write!(f, "host function"),
+    }
+}
+
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            TraceEvent::Call {
+                contract_id,
+                function,
+                args,
+            } => {
+                write!(f, "call ")?;
+                fmt_function(f, contract_id, function)?;
+                write!(f, "(")?;
+                fmt_list(f, args.iter(), |f, a| PrettyScVal(a).fmt(f))?;
+                write!(f, ")")
+            }
+            TraceEvent::Return {
+                contract_id,
+                function,
+                result,
+                cpu_insns,
+                mem_bytes,
+            } => {
+                write!(f, "return from ")?;
+                fmt_function(f, contract_id, function)?;
+                match result {
+                    Ok(val) => write!(f, ": {}", PrettyScVal(val))?,
+                    Err(st) => write!(f, ": error {:?}", Status::from(st.clone()))?,
+                }
+                write!(f, " (cpu insns: {}, mem bytes: {})", cpu_insns, mem_bytes)
+            }
+        }
+    }
+}
+
+impl Display for LogEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let target: SymbolStr = self.target.into();
+        let target: &str = target.as_ref();
+        write!(f, "{} {}", self.level.name(), target)?;
+        if let Some(id) = &self.contract_id {
+            write!(f, " from {}", contract_id_to_strkey(id))?;
+        }
+        write!(f, ": ")?;
+        fmt_list(f, self.values.iter(), |f, v| PrettyScVal(v).fmt(f))
+    }
+}
+
+/// Renders the contract, diagnostic, trace and log events in full. The arguments
+/// of the debug events are raw values, which can only be rendered in full
+/// with the host they belong to (see [`Host::render_event`]). The events
+/// emitted within the failed contract calls are prefixed with `[failed call]`.
+impl Display for HostEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if !self.in_successful_contract_call {
+            write!(f, "[failed call] ")?;
+        }
+        match &self.event {
+            Event::Contract(ce) | Event::Diagnostic(ce) => PrettyContractEvent(ce).fmt(f),
+            Event::Debug(de) => write!(f, "debug event: {}", de),
+            Event::Trace(te) => te.fmt(f),
+            Event::Log(le) => le.fmt(f),
+        }
+    }
+}
+
+impl Host {
+    /// Renders a [`RawVal`] the same way as [`PrettyScVal`]. Values that can't
+    /// be converted to `ScVal` (e.g. the rolled back objects) are rendered in
+    /// their raw form.
+    // Metering: the conversion is charged to the shadow budget.
+    pub fn render_val(&self, val: RawVal) -> String {
+        let mut rendered = None;
+        let _ = self.with_debug_budget(|| {
+            rendered = Some(PrettyScVal(&self.from_host_val(val)?).to_string());
+            Ok(())
+        });
+        rendered.unwrap_or_else(|| format!("{:?}", val))
+    }
+
+    /// Renders a [`HostEvent`], including the arguments of the debug events,
+    /// which are rendered with [`Host::render_val`].
+    pub fn render_event(&self, event: &HostEvent) -> String {
+        match &event.event {
+            Event::Debug(de) => format!(
+                "{}debug event: {}",
+                if event.in_successful_contract_call {
+                    ""
+                } else {
+                    "[failed call] "
+                },
+                self.render_debug_event(de)
+            ),
+            _ => event.to_string(),
+        }
+    }
+
+    fn render_debug_event(&self, event: &DebugEvent) -> String {
+        let args: Vec<String> = event
+            .args
+            .iter()
+            .map(|arg| match arg {
+                DebugArg::Str(s) => s.to_string(),
+                DebugArg::Val(rv) => self.render_val(*rv),
+            })
+            .collect();
+        match &event.msg {
+            None => args.concat(),
+            Some(fmt) => dyn_fmt::Arguments::new(fmt, args.as_slice()).to_string(),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use super::HostEvent;
+
+/// Determines when the events are handed to an [`EventSink`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventSinkMode {
+    /// The events are handed over once the top-level frame they have been
+    /// recorded in has finished (i.e. when they can no longer be rolled back),
+    /// and when the host is finished.
+    OnCommit,
+    /// The events are handed over at every frame boundary, i.e. when a
+    /// contract call starts or finishes. A frame may still be rolled back
+    /// afterwards, in which case the sink is notified via
+    /// [`EventSink::rollback`].
+    Immediate,
+}
+
+/// Receives the host events during the execution, so that they can be
+/// streamed elsewhere (e.g. to a log or a database) while the host is still
+/// running.
+///
+/// The events handed to the sink are still kept by the host, so they are
+/// returned by [`Host::get_events`](crate::Host::get_events) and
+/// [`Host::try_finish`](crate::Host::try_finish) as well.
+pub trait EventSink {
+    /// Receives the next event, in the chronological order.
+    fn receive(&self, event: HostEvent);
+
+    /// Notifies the sink that a frame has been rolled back, and the last
+    /// `count` events it has received have been emitted within the failed
+    /// call. The contract events among these are not going to make it to the
+    /// ledger. Only called in [`EventSinkMode::Immediate`].
+    fn rollback(&self, _count: usize) {}
+}
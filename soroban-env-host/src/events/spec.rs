@@ -0,0 +1,123 @@
+use crate::json;
+use crate::xdr::{ContractEvent, ContractEventBody, ScObject, ScStatic, ScVal};
+
+/// The type of a topic or the data of a native contract event.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventValueType {
+    Symbol,
+    Address,
+    I128,
+    Bool,
+}
+
+impl EventValueType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventValueType::Symbol => "Symbol",
+            EventValueType::Address => "Address",
+            EventValueType::I128 => "I128",
+            EventValueType::Bool => "Bool",
+        }
+    }
+
+    fn matches(&self, val: &ScVal) -> bool {
+        matches!(
+            (self, val),
+            (EventValueType::Symbol, ScVal::Symbol(_))
+                | (
+                    EventValueType::Address,
+                    ScVal::Object(Some(ScObject::Address(_)))
+                )
+                | (EventValueType::I128, ScVal::Object(Some(ScObject::I128(_))))
+                | (
+                    EventValueType::Bool,
+                    ScVal::Static(ScStatic::True | ScStatic::False)
+                )
+        )
+    }
+}
+
+/// A named topic or the data of a native contract event.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EventParamSpec {
+    pub name: &'static str,
+    pub type_: EventValueType,
+}
+
+impl EventParamSpec {
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"name\":");
+        json::write_string(out, self.name);
+        out.push_str(",\"type\":");
+        json::write_string(out, self.type_.name());
+        out.push('}');
+    }
+}
+
+/// The machine-readable description of a native contract event, generated by
+/// the `#[contractevent]` macro. The topics of the event are its name (as a
+/// `Symbol`) followed by the values described by `topics`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ContractEventSpec {
+    pub name: &'static str,
+    pub topics: &'static [EventParamSpec],
+    pub data: EventParamSpec,
+}
+
+impl ContractEventSpec {
+    /// Decodes `event` into the named topic values followed by the named data,
+    /// or returns `None` if the event doesn't have the described shape.
+    pub fn decode(&self, event: &ContractEvent) -> Option<Vec<(&'static str, ScVal)>> {
+        let ContractEventBody::V0(body) = &event.body;
+        let (name, topics) = body.topics.0.split_first()?;
+        match name {
+            ScVal::Symbol(s) if s.as_slice() == self.name.as_bytes() => (),
+            _ => return None,
+        }
+        if topics.len() != self.topics.len() || !self.data.type_.matches(&body.data) {
+            return None;
+        }
+        let mut decoded = Vec::with_capacity(topics.len() + 1);
+        for (spec, val) in self.topics.iter().zip(topics.iter()) {
+            if !spec.type_.matches(val) {
+                return None;
+            }
+            decoded.push((spec.name, val.clone()));
+        }
+        decoded.push((self.data.name, body.data.clone()));
+        Some(decoded)
+    }
+
+    /// Renders the description as a JSON object with `name`, `topics` and
+    /// `data` fields. The topics and the data are objects with `name` and
+    /// `type` fields, where `type` is the name of the [`EventValueType`].
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"name\":");
+        json::write_string(out, self.name);
+        out.push_str(",\"topics\":");
+        json::write_array(out, self.topics, |out, topic| topic.write_json(out));
+        out.push_str(",\"data\":");
+        self.data.write_json(out);
+        out.push('}');
+    }
+}
+
+/// Renders the descriptions as a JSON array of the objects produced by
+/// [ContractEventSpec::to_json].
+pub fn contract_event_specs_to_json(specs: &[ContractEventSpec]) -> String {
+    let mut out = String::new();
+    json::write_array(&mut out, specs, |out, spec| spec.write_json(out));
+    out
+}
+
+/// Returns the descriptions of all the events emitted by the native token
+/// contract.
+pub fn token_event_specs() -> &'static [ContractEventSpec] {
+    crate::native_contract::token::EVENT_SPECS
+}
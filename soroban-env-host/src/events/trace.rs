@@ -0,0 +1,31 @@
+use crate::{
+    xdr::{Hash, ScStatus, ScVal},
+    Symbol,
+};
+
+/// A structured record of a frame being pushed to or popped from the host
+/// context. Recorded only when enabled via
+/// [Host::enable_call_trace_events](crate::Host::enable_call_trace_events).
+///
+/// Host function frames have neither a contract id nor a function name. The
+/// values and errors that can't be converted to XDR are recorded as
+/// `ScStatus::UnknownError(ScUnknownErrorCode::General)`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TraceEvent {
+    /// A frame has been pushed, i.e. a function is about to be called.
+    Call {
+        contract_id: Option<Hash>,
+        function: Option<Symbol>,
+        args: Vec<ScVal>,
+    },
+    /// A frame has been popped, i.e. a function has returned with either a
+    /// value or an error. `cpu_insns` and `mem_bytes` are the amounts of the
+    /// budget consumed within the frame (including any nested frames).
+    Return {
+        contract_id: Option<Hash>,
+        function: Option<Symbol>,
+        result: Result<ScVal, ScStatus>,
+        cpu_insns: u64,
+        mem_bytes: u64,
+    },
+}
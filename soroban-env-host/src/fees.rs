@@ -0,0 +1,193 @@
+//! This module contains [FeeSchedule] and [compute_fee], which translate the
+//! resources consumed by a completed invocation -- as summarized in
+//! [InvocationResources] -- into an itemized fee.
+//!
+//! Fees are split into a _non-refundable_ part, which covers resources that
+//! are declared up front (compute, ledger reads and ledger writes), and a
+//! _refundable_ part, which covers resources whose exact size is only known
+//! after execution (currently contract events).
+
+use crate::budget::Budget;
+use crate::events::{Event, Events, HostEvent};
+use crate::storage::{AccessType, Storage};
+use crate::xdr::{ScUnknownErrorCode, WriteXdr};
+use crate::HostError;
+
+/// Number of CPU instructions charged per `fee_per_instruction_increment`.
+pub const INSTRUCTIONS_INCREMENT: u64 = 10_000;
+/// Number of bytes charged per each of the `*_1kb` fee rates.
+pub const DATA_SIZE_1KB_INCREMENT: u64 = 1024;
+
+/// Resources consumed by a single invocation that are subject to fees.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InvocationResources {
+    pub cpu_insns: u64,
+    pub mem_bytes: u64,
+    /// Number of ledger entries in the footprint (all of which are read).
+    pub read_entries: u32,
+    /// Number of ledger entries in the footprint with read-write access.
+    pub write_entries: u32,
+    /// Total XDR size of the keys and entries in the footprint.
+    pub read_bytes: u64,
+    /// Total XDR size of the entries written.
+    pub write_bytes: u64,
+    /// Total XDR size of the contract events emitted.
+    pub contract_events_bytes: u64,
+}
+
+fn xdr_len(obj: &impl WriteXdr) -> Result<u64, HostError> {
+    let mut buf = Vec::new();
+    obj.write_xdr(&mut buf)
+        .map_err(|_| HostError::from(ScUnknownErrorCode::Xdr))?;
+    Ok(buf.len() as u64)
+}
+
+impl InvocationResources {
+    /// Measures the resources of a completed invocation from the parts
+    /// returned by [Host::try_finish](crate::Host::try_finish).
+    ///
+    /// Entry sizes are taken from the storage map as it stands after the
+    /// invocation, so an entry that was both read and rewritten is measured
+    /// at its new size. Embedders that know the pre-invocation sizes can
+    /// overwrite `read_bytes` before computing the fee.
+    ///
+    /// This is an accounting step performed outside of contract execution
+    /// and is therefore not metered.
+    pub fn from_invocation(
+        budget: &Budget,
+        storage: &Storage,
+        events: &Events,
+    ) -> Result<Self, HostError> {
+        let mut res = InvocationResources {
+            cpu_insns: budget.get_cpu_insns_count(),
+            mem_bytes: budget.get_mem_bytes_count(),
+            ..Default::default()
+        };
+        for (key, access) in &storage.footprint.0 {
+            let entry = storage.map.map.iter().find(|(k, _)| k == key);
+            let entry_len = match entry {
+                Some((_, Some(le))) => xdr_len(le.as_ref())?,
+                _ => 0,
+            };
+            res.read_entries = res.read_entries.saturating_add(1);
+            res.read_bytes = res
+                .read_bytes
+                .saturating_add(xdr_len(key.as_ref())?)
+                .saturating_add(entry_len);
+            if *access == AccessType::ReadWrite {
+                res.write_entries = res.write_entries.saturating_add(1);
+                res.write_bytes = res.write_bytes.saturating_add(entry_len);
+            }
+        }
+        for e in events.0.iter() {
+            if let HostEvent {
+                event: Event::Contract(ce),
+                in_successful_contract_call: true,
+            } = e
+            {
+                res.contract_events_bytes = res.contract_events_bytes.saturating_add(xdr_len(ce)?);
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// Network-configured fee rates, in stroops.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// Fee per `INSTRUCTIONS_INCREMENT` CPU instructions.
+    pub fee_per_instruction_increment: i64,
+    /// Fee per 1KB of modeled memory.
+    pub fee_per_mem_1kb: i64,
+    /// Fee per ledger entry read.
+    pub fee_per_read_entry: i64,
+    /// Fee per ledger entry written.
+    pub fee_per_write_entry: i64,
+    /// Fee per 1KB read from the ledger.
+    pub fee_per_read_1kb: i64,
+    /// Fee per 1KB written to the ledger.
+    pub fee_per_write_1kb: i64,
+    /// Fee per 1KB of contract events emitted.
+    pub fee_per_contract_event_1kb: i64,
+}
+
+/// Itemized fee for an invocation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    pub compute: i64,
+    pub read: i64,
+    pub write: i64,
+    pub events: i64,
+}
+
+impl FeeBreakdown {
+    /// Fee for resources declared before execution: compute, reads and writes.
+    pub fn non_refundable(&self) -> i64 {
+        self.compute
+            .saturating_add(self.read)
+            .saturating_add(self.write)
+    }
+
+    /// Fee for resources only known after execution: events.
+    pub fn refundable(&self) -> i64 {
+        self.events
+    }
+
+    pub fn total(&self) -> i64 {
+        self.non_refundable().saturating_add(self.refundable())
+    }
+}
+
+// Computes `ceil(amount * rate / increment)`, saturating at i64::MAX.
+fn scaled_fee(amount: u64, rate: i64, increment: u64) -> i64 {
+    let num = (amount as i128).saturating_mul(rate.max(0) as i128);
+    let denom = increment as i128;
+    let fee = num.saturating_add(denom - 1) / denom;
+    fee.min(i64::MAX as i128) as i64
+}
+
+/// Computes the itemized fee of `resources` under `schedule`. All
+/// components round up and saturate rather than overflow.
+pub fn compute_fee(resources: &InvocationResources, schedule: &FeeSchedule) -> FeeBreakdown {
+    let compute = scaled_fee(
+        resources.cpu_insns,
+        schedule.fee_per_instruction_increment,
+        INSTRUCTIONS_INCREMENT,
+    )
+    .saturating_add(scaled_fee(
+        resources.mem_bytes,
+        schedule.fee_per_mem_1kb,
+        DATA_SIZE_1KB_INCREMENT,
+    ));
+    let read = scaled_fee(
+        resources.read_entries as u64,
+        schedule.fee_per_read_entry,
+        1,
+    )
+    .saturating_add(scaled_fee(
+        resources.read_bytes,
+        schedule.fee_per_read_1kb,
+        DATA_SIZE_1KB_INCREMENT,
+    ));
+    let write = scaled_fee(
+        resources.write_entries as u64,
+        schedule.fee_per_write_entry,
+        1,
+    )
+    .saturating_add(scaled_fee(
+        resources.write_bytes,
+        schedule.fee_per_write_1kb,
+        DATA_SIZE_1KB_INCREMENT,
+    ));
+    let events = scaled_fee(
+        resources.contract_events_bytes,
+        schedule.fee_per_contract_event_1kb,
+        DATA_SIZE_1KB_INCREMENT,
+    );
+    FeeBreakdown {
+        compute,
+        read,
+        write,
+        events,
+    }
+}
@@ -12,25 +12,29 @@ use soroban_env_common::{
         CreateContractArgs, ExtensionPoint, Hash, HashIdPreimage, HostFunction, HostFunctionType,
         InstallContractCodeArgs, Int128Parts, LedgerEntryData, LedgerKey, LedgerKeyContractCode,
         ScAddress, ScContractCode, ScHostContextErrorCode, ScHostFnErrorCode, ScHostObjErrorCode,
-        ScHostStorageErrorCode, ScHostValErrorCode, ScMap, ScMapEntry, ScObject, ScStatusType,
-        ScUnknownErrorCode, ScVal, ScVec,
+        ScHostStorageErrorCode, ScHostValErrorCode, ScMap, ScMapEntry, ScObject, ScStatus,
+        ScStatusType, ScUnknownErrorCode, ScVal, ScVec, ThresholdIndexes,
     },
     Convert, InvokerType, Status, TryFromVal, TryIntoVal, VmCaller, VmCallerEnv,
 };
 
-use crate::auth::{AuthorizationManager, AuthorizationManagerSnapshot, RecordedAuthPayload};
+use crate::auth::{
+    export::AuthorizationTree, AuthorizationManager, AuthorizationManagerSnapshot,
+    RecordedAuthPayload,
+};
 use crate::budget::{AsBudget, Budget, CostType};
 use crate::events::{
-    DebugError, DebugEvent, Events, InternalContractEvent, InternalEvent, InternalEventsBuffer,
+    ContractEventLimits, DebugError, DebugEvent, EventSink, EventSinkMode, Events,
+    InternalContractEvent, InternalDebugEvent, InternalEvent, InternalEventsBuffer,
+    InternalLogEvent, InternalTraceEvent, LogEvent, LogLevel, TraceEvent,
 };
 use crate::storage::{Storage, StorageMap};
 
 use crate::host_object::{HostMap, HostObject, HostObjectType, HostVec};
-#[cfg(feature = "vm")]
-use crate::SymbolStr;
+use crate::native_contract::account_contract::{AccountSigners, CheckAuthFrame};
 #[cfg(feature = "vm")]
 use crate::Vm;
-use crate::{EnvBase, Object, RawVal, RawValConvertible, Symbol};
+use crate::{EnvBase, Object, RawVal, RawValConvertible, Symbol, SymbolStr};
 
 pub(crate) mod comparison;
 mod conversion;
@@ -108,6 +112,20 @@ pub(crate) enum Frame {
     TestContract(TestContractFrame),
 }
 
+/// State of a traced frame captured when it is pushed, see
+/// [`Host::enable_call_trace_events`].
+struct CallTrace {
+    contract_id: Option<Hash>,
+    function: Option<Symbol>,
+    cpu_insns: u64,
+    mem_bytes: u64,
+}
+
+// Recorded in the trace events in place of the values and the errors that
+// can't be converted to XDR, as tracing must never change the outcome of the
+// traced call.
+const UNTRACEABLE_STATUS: ScStatus = ScStatus::UnknownError(ScUnknownErrorCode::General);
+
 /// Temporary helper for denoting a slice of guest memory, as formed by
 /// various bytes operations.
 #[cfg(feature = "vm")]
@@ -130,6 +148,7 @@ pub struct LedgerInfo {
 pub(crate) struct HostImpl {
     source_account: RefCell<Option<AccountId>>,
     ledger: RefCell<Option<LedgerInfo>>,
+    event_limits: RefCell<ContractEventLimits>,
     objects: RefCell<Vec<HostObject>>,
     storage: RefCell<Storage>,
     pub(crate) context: RefCell<Vec<Frame>>,
@@ -139,7 +158,16 @@ pub(crate) struct HostImpl {
     // actually wants their clones to be metered by "the same" total budget
     pub(crate) budget: Budget,
     pub(crate) events: RefCell<InternalEventsBuffer>,
-    authorization_manager: RefCell<AuthorizationManager>,
+    event_sink: RefCell<Option<(Rc<dyn EventSink>, EventSinkMode)>>,
+    pub(crate) authorization_manager: RefCell<AuthorizationManager>,
+    // Thresholds and signer weights of the classic accounts that have been
+    // loaded for authentication during the current invocation. Contracts can't
+    // modify these, so every account needs to be read from the ledger at most
+    // once per invocation.
+    account_signers: RefCell<Vec<(AccountId, Rc<AccountSigners>)>>,
+    // Account contract `check_auth` calls that are currently running, used to
+    // serve `get_auth_policy_context`.
+    check_auth_frames: RefCell<Vec<CheckAuthFrame>>,
     // Note: we're not going to charge metering for testutils because it's out of the scope
     // of what users will be charged for in production -- it's scaffolding for testing a contract,
     // but shouldn't be charged to the contract itself (and will never be compiled-in to
@@ -207,14 +235,18 @@ impl Host {
         Self(Rc::new(HostImpl {
             source_account: RefCell::new(None),
             ledger: RefCell::new(None),
+            event_limits: Default::default(),
             objects: Default::default(),
             storage: RefCell::new(storage),
             context: Default::default(),
             budget: budget.clone(),
             events: Default::default(),
+            event_sink: Default::default(),
             authorization_manager: RefCell::new(
                 AuthorizationManager::new_enforcing_without_authorizations(budget),
             ),
+            account_signers: Default::default(),
+            check_auth_frames: Default::default(),
             #[cfg(any(test, feature = "testutils"))]
             contracts: Default::default(),
             #[cfg(any(test, feature = "testutils"))]
@@ -244,6 +276,24 @@ impl Host {
             AuthorizationManager::new_recording(self.budget_cloned());
     }
 
+    /// Like [`Host::switch_to_recording_auth`], but records unordered nonces
+    /// (see [`crate::auth::UNORDERED_NONCE_FLAG`]) instead of the sequential
+    /// ones. The nonces are derived from `nonce_seed`, which should be random,
+    /// and the recorded payloads expire `signature_lifetime_ledgers` after the
+    /// current ledger.
+    pub fn switch_to_recording_auth_with_unordered_nonces(
+        &self,
+        nonce_seed: [u8; 32],
+        signature_lifetime_ledgers: u32,
+    ) {
+        *self.0.authorization_manager.borrow_mut() =
+            AuthorizationManager::new_recording_with_unordered_nonces(
+                self.budget_cloned(),
+                nonce_seed,
+                signature_lifetime_ledgers,
+            );
+    }
+
     pub fn set_authorization_entries(
         &self,
         auth_entries: Vec<soroban_env_common::xdr::ContractAuth>,
@@ -253,10 +303,44 @@ impl Host {
         Ok(())
     }
 
+    /// Sets up enforcing-mode authorization for preflight: the entries are
+    /// enforced just like in [`Host::set_authorization_entries`],
+    /// but the signatures in them are not verified. The budget is still
+    /// charged for the verification, so that the resulting cost estimate
+    /// matches the one of the signed transaction as closely as possible.
+    ///
+    /// For the classic accounts, the signatures of the highest-weight signers
+    /// needed to reach the account threshold are charged for. For the custom
+    /// accounts `check_auth` is called with the provided signature arguments
+    /// (that may be dummy) and any failure other than running out of budget
+    /// is ignored.
+    pub fn set_authorization_entries_for_simulation(
+        &self,
+        auth_entries: Vec<soroban_env_common::xdr::ContractAuth>,
+    ) -> Result<(), HostError> {
+        let new_auth_manager =
+            AuthorizationManager::new_enforcing_for_simulation(self, auth_entries)?;
+        *self.0.authorization_manager.borrow_mut() = new_auth_manager;
+        Ok(())
+    }
+
     pub fn set_ledger_info(&self, info: LedgerInfo) {
         *self.0.ledger.borrow_mut() = Some(info)
     }
 
+    /// Sets the limits the contract events are validated against.
+    pub fn set_contract_event_limits(&self, limits: ContractEventLimits) {
+        *self.0.event_limits.borrow_mut() = limits
+    }
+
+    /// Makes the host hand the events over to `sink` as they are produced,
+    /// according to `mode` (see [`EventSink`]). The events recorded before
+    /// the sink has been set are handed over the next time the sink receives
+    /// anything.
+    pub fn set_event_sink(&self, sink: Rc<dyn EventSink>, mode: EventSinkMode) {
+        *self.0.event_sink.borrow_mut() = Some((sink, mode))
+    }
+
     pub fn with_ledger_info<F, T>(&self, f: F) -> Result<T, HostError>
     where
         F: FnOnce(&LedgerInfo) -> Result<T, HostError>,
@@ -299,6 +383,25 @@ impl Host {
         self.0.budget.clone().charge(ty, input)
     }
 
+    /// Runs `f` (typically an invocation) while cross-checking the memory
+    /// charged to the budget against real allocations reported by `counter`,
+    /// and returns the result of `f` along with a report of the cost types
+    /// whose charged and measured memory differ the most.
+    #[cfg(any(test, feature = "testutils"))]
+    pub fn with_allocation_check<T, F>(
+        &self,
+        counter: Rc<dyn crate::budget::AllocationCounter>,
+        f: F,
+    ) -> (T, crate::budget::AllocationReport)
+    where
+        F: FnOnce(&Host) -> T,
+    {
+        self.0.budget.enable_allocation_check(counter);
+        let res = f(self);
+        let report = self.0.budget.disable_allocation_check().unwrap_or_default();
+        (res, report)
+    }
+
     pub(crate) fn get_events_mut<F, U>(&self, f: F) -> Result<U, HostError>
     where
         F: FnOnce(&mut InternalEventsBuffer) -> Result<U, HostError>,
@@ -317,17 +420,132 @@ impl Host {
     where
         DebugEvent: From<T>,
     {
-        // We want to record an event _before_ we charge the budget, to maximize
-        // the chance we return "what the contract was doing when it ran out of
-        // gas" in cases it does. This does mean in that one case we'll exceed
-        // the gas limit a tiny amount (one event-worth) but it's not something
-        // users can harm us with nor does it observably effect the order the
-        // contract runs out of gas in; this is an atomic action from the
-        // contract's perspective.
+        // Debug events are charged to the shadow budget (before they are
+        // recorded), and are silently dropped if it is exhausted, so that
+        // diagnostics never change whether or when the contract itself runs
+        // out of budget. The main budget running out is still explained by
+        // the debug event recorded with the resulting error, as long as the
+        // shadow budget allows it.
         let event: DebugEvent = src.into();
+        self.with_debug_budget(|| {
+            self.charge_budget(CostType::HostEventDebug, 1)?;
+            // The arguments are converted right away, as the objects they refer
+            // to may be rolled back by the time the events are externalized.
+            let diagnostic = if self.0.events.borrow().xdr_diagnostics {
+                Some(InternalDebugEvent::to_diagnostic(
+                    self,
+                    self.get_current_contract_id_opt_internal()?,
+                    &event,
+                    true,
+                )?)
+            } else {
+                None
+            };
+            let event = InternalDebugEvent {
+                event,
+                diagnostic,
+                in_successful_contract_call: true,
+            };
+            self.get_events_mut(|events| {
+                Ok(events.record(InternalEvent::Debug(event), self.as_budget()))
+            })?
+        })?;
+        Ok(())
+    }
+
+    /// Makes the host convert the debug events recorded from now on to the XDR
+    /// contract events of `Diagnostic` type (see
+    /// [`Event::Diagnostic`](crate::events::Event::Diagnostic))
+    /// instead of [`Event::Debug`](crate::events::Event::Debug), so
+    /// that they can be serialized and consumed outside of the host (e.g.
+    /// returned from the simulation).
+    pub fn enable_xdr_diagnostic_events(&self) -> Result<(), HostError> {
+        self.get_events_mut(|events| {
+            events.xdr_diagnostics = true;
+            Ok(())
+        })
+    }
+
+    /// Makes the host drop the contract log records (see
+    /// [`LogEvent`](crate::events::LogEvent)) below `level` instead of
+    /// recording them. All the records are recorded by default.
+    pub fn set_min_log_level(&self, level: LogLevel) -> Result<(), HostError> {
         self.get_events_mut(|events| {
-            Ok(events.record(InternalEvent::Debug(event), self.as_budget()))
-        })?
+            events.min_log_level = level;
+            Ok(())
+        })
+    }
+
+    // Records a structured log record emitted by the current contract, and
+    // routes it to the `log` crate with the `target` as the log target.
+    // `values` is validated regardless of the log level and the logger, so
+    // that the outcome of the call doesn't depend on the logging setup.
+    // Metering: the validation is charged to the main budget. Like the debug
+    // events, the log records are charged to the shadow budget, and are
+    // silently dropped if it is exhausted or they can't be recorded.
+    pub(crate) fn record_log_event(
+        &self,
+        level: LogLevel,
+        target: Symbol,
+        values: Object,
+    ) -> Result<(), HostError> {
+        self.visit_obj(values, |_: &HostVec| Ok(()))?;
+        let target_str: SymbolStr = target.into();
+        let target_str: &str = target_str.as_ref();
+        let record = level >= self.0.events.borrow().min_log_level;
+        let route = log::log_enabled!(target: target_str, log::Level::from(level));
+        if !record && !route {
+            return Ok(());
+        }
+        let _ = self.with_debug_budget(|| {
+            self.charge_budget(CostType::HostEventDebug, 1)?;
+            let values = self
+                .visit_obj(values, |hv: &HostVec| Ok(hv.clone()))?
+                .iter()
+                .map(|v| self.from_host_val(*v))
+                .collect::<Result<Vec<ScVal>, HostError>>()?;
+            let event = LogEvent {
+                level,
+                contract_id: self.get_current_contract_id_opt_internal()?,
+                target,
+                values,
+            };
+            if route {
+                log::log!(target: target_str, log::Level::from(level), "{}", event);
+            }
+            if !record {
+                return Ok(());
+            }
+            let diagnostic = if self.0.events.borrow().xdr_diagnostics {
+                Some(event.to_diagnostic(self)?)
+            } else {
+                None
+            };
+            self.get_events_mut(|events| {
+                events.record(
+                    InternalEvent::Log(InternalLogEvent {
+                        event,
+                        diagnostic,
+                        in_successful_contract_call: true,
+                    }),
+                    self.as_budget(),
+                )
+            })
+        });
+        Ok(())
+    }
+
+    /// Runs `f` -- some diagnostic-only work -- against the shadow budget
+    /// (see [`Budget::with_shadow_mode`]). If the shadow budget is exhausted
+    /// the work is abandoned and `Ok(())` is returned.
+    pub(crate) fn with_debug_budget<F>(&self, f: F) -> Result<(), HostError>
+    where
+        F: FnOnce() -> Result<(), HostError>,
+    {
+        match self.as_budget().with_shadow_mode(f) {
+            Err(e) if e.is_budget_exceeded() => Ok(()),
+            res => res,
+        }
     }
 
     // Records a contract event.
@@ -337,16 +555,73 @@ impl Host {
         topics: Object,
         data: RawVal,
     ) -> Result<(), HostError> {
-        self.validate_contract_event_topics(topics)?;
+        let size = self.validate_contract_event_topics(topics)?
+            + self.validate_contract_event_data(data)?;
+        self.validate_invocation_events_usage(size)?;
         let ce = InternalContractEvent {
             type_,
             contract_id: self.obj_from_internal_contract_id()?,
             topics,
             data,
+            size,
         };
-        self.get_events_mut(|events| {
-            Ok(events.record(InternalEvent::Contract(ce), self.as_budget()))
-        })?
+        self.get_events_mut(|events| events.record(InternalEvent::Contract(ce), self.as_budget()))?;
+        Ok(())
+    }
+
+    // Hands the events that have been recorded since the last call over to
+    // the event sink (if there is one). This is called at the frame
+    // boundaries; in the `OnCommit` mode the events are only handed over when
+    // `commit` is set, i.e. when no frame can be rolled back anymore. The
+    // events are kept in the buffer, and only the number of the handed over
+    // ones is tracked. Delivery never fails: if an event can't be converted
+    // (e.g. when the shadow budget is exhausted), it and the following events
+    // are handed over by the next call. If the sink calls back into the host,
+    // the events are handed over in order by whichever call gets to them
+    // first.
+    // Metering: the conversions are charged to the shadow budget.
+    fn deliver_events_to_sink(&self, commit: bool) {
+        let (sink, mode) = match self.0.event_sink.borrow().as_ref() {
+            Some((sink, mode)) => (sink.clone(), *mode),
+            None => return,
+        };
+        if !commit && mode == EventSinkMode::OnCommit {
+            return;
+        }
+        let (start, events) = {
+            let buffer = self.0.events.borrow();
+            (buffer.sink_delivered, buffer.undelivered_events())
+        };
+        let mut host_events = Vec::with_capacity(events.len());
+        let _ = self.with_debug_budget(|| {
+            for e in events.iter() {
+                host_events.push(e.to_host_event(self)?);
+            }
+            Ok(())
+        });
+        for (i, e) in host_events.into_iter().enumerate() {
+            {
+                let mut buffer = self.0.events.borrow_mut();
+                if buffer.sink_delivered != start + i {
+                    return;
+                }
+                buffer.sink_delivered += 1;
+            }
+            sink.receive(e);
+        }
+    }
+
+    // Notifies the event sink that the events starting at `events` have been
+    // rolled back.
+    fn rollback_events_in_sink(&self, events: usize) {
+        let sink = match self.0.event_sink.borrow().as_ref() {
+            Some((sink, _)) => sink.clone(),
+            None => return,
+        };
+        let delivered = self.0.events.borrow().sink_delivered;
+        if delivered > events {
+            sink.rollback(delivered - events);
+        }
     }
 
     pub fn with_mut_storage<F, U>(&self, f: F) -> Result<U, HostError>
@@ -361,6 +636,7 @@ impl Host {
     /// caller as a tuple wrapped in `Ok(...)`. If the provided host reference
     /// is not unique, returns `Err(self)`.
     pub fn try_finish(self) -> Result<(Storage, Budget, Events), (Self, HostError)> {
+        self.deliver_events_to_sink(true);
         let events = self
             .0
             .events
@@ -398,7 +674,7 @@ impl Host {
         Ok(RollbackPoint {
             objects: self.0.objects.borrow().len(),
             storage: self.0.storage.borrow().map.clone(),
-            events: self.0.events.borrow().vec.len(),
+            events: self.0.events.borrow().len(),
             auth: auth_snapshot,
         })
     }
@@ -427,6 +703,7 @@ impl Host {
                 .authorization_manager
                 .borrow_mut()
                 .maybe_emulate_authentication(self)?;
+            self.0.account_signers.borrow_mut().clear();
             // Empty call stack in tests means that some contract function call
             // has been finished and hence the authorization manager can be reset.
             // In non-test scenarios, there should be no need to ever reset
@@ -441,13 +718,29 @@ impl Host {
         }
 
         if let Some(rp) = orp {
+            // Contract events refer to the objects, so they have to be
+            // converted before the objects are rolled back.
+            // Events that fail to convert are recorded as debug events instead.
+            // Metering: the conversions are charged to the shadow budget.
+            let contract_events = self.0.events.borrow().contract_events_since(rp.events);
+            let rolled_back_events: Vec<_> = contract_events
+                .into_iter()
+                .map(|c| self.as_budget().with_shadow_mode(|| c.to_xdr(self)).ok())
+                .collect();
             self.0.objects.borrow_mut().truncate(rp.objects);
             self.0.storage.borrow_mut().map = rp.storage;
-            self.0.events.borrow_mut().rollback(rp.events, self)?;
+            self.0
+                .events
+                .borrow_mut()
+                .rollback(rp.events, rolled_back_events, self)?;
+            self.rollback_events_in_sink(rp.events);
             if let Some(auth_rp) = rp.auth {
                 self.0.authorization_manager.borrow_mut().rollback(auth_rp);
             }
         }
+        if self.0.context.borrow().is_empty() {
+            self.0.events.borrow_mut().reset_invocation_usage();
+        }
         Ok(())
     }
 
@@ -489,7 +782,9 @@ impl Host {
     {
         self.charge_budget(CostType::GuardFrame, 1)?;
         let start_depth = self.0.context.borrow().len();
+        let call_trace = self.start_call_trace(&frame);
         let rp = self.push_frame(frame)?;
+        self.deliver_events_to_sink(false);
         let res = f();
         let res = match res {
             Ok(v) if v.is::<Status>() => {
@@ -514,9 +809,108 @@ impl Host {
         // Every push and pop should be matched; if not there is a bug.
         let end_depth = self.0.context.borrow().len();
         assert_eq!(start_depth, end_depth);
+        if let Some(call_trace) = call_trace {
+            self.finish_call_trace(call_trace, &res);
+        }
+        self.deliver_events_to_sink(start_depth == 0);
         res
     }
 
+    /// Makes the host record a [`TraceEvent`](crate::events::TraceEvent) on
+    /// every frame push and pop, which provides the full trace of the
+    /// (cross-)contract calls in the [`Events`].
+    pub fn enable_call_trace_events(&self) -> Result<(), HostError> {
+        self.get_events_mut(|events| {
+            events.call_trace = true;
+            Ok(())
+        })
+    }
+
+    // Records the `Call` trace event for the frame that is about to be pushed
+    // (if call tracing is enabled) and returns the state needed to record the
+    // matching `Return` event. Failures to record the event are ignored.
+    // Metering: the conversions are charged to the shadow budget.
+    fn start_call_trace(&self, frame: &Frame) -> Option<CallTrace> {
+        if !self.0.events.borrow().call_trace {
+            return None;
+        }
+        let (contract_id, function, args) = match frame {
+            #[cfg(feature = "vm")]
+            Frame::ContractVM(vm, func, args) => {
+                (Some(vm.contract_id.clone()), Some(*func), args.as_slice())
+            }
+            Frame::HostFunction(_) => (None, None, [].as_slice()),
+            Frame::Token(id, func, args) => (Some(id.clone()), Some(*func), args.as_slice()),
+            #[cfg(any(test, feature = "testutils"))]
+            Frame::TestContract(tc) => (Some(tc.id.clone()), Some(tc.func), tc.args.as_slice()),
+        };
+        let _ = self.with_debug_budget(|| {
+            let args = args.iter().map(|a| self.trace_val(*a)).collect();
+            self.get_events_mut(|events| {
+                events.record(
+                    InternalEvent::Trace(InternalTraceEvent {
+                        event: TraceEvent::Call {
+                            contract_id: contract_id.clone(),
+                            function,
+                            args,
+                        },
+                        in_successful_contract_call: true,
+                    }),
+                    self.as_budget(),
+                )
+            })
+        });
+        Some(CallTrace {
+            contract_id,
+            function,
+            cpu_insns: self.0.budget.get_cpu_insns_count(),
+            mem_bytes: self.0.budget.get_mem_bytes_count(),
+        })
+    }
+
+    // Records the `Return` trace event for the frame that has just been popped.
+    // Failures to record the event are ignored.
+    // Metering: the conversions are charged to the shadow budget.
+    fn finish_call_trace(&self, call_trace: CallTrace, res: &Result<RawVal, HostError>) {
+        let cpu_insns = self
+            .0
+            .budget
+            .get_cpu_insns_count()
+            .saturating_sub(call_trace.cpu_insns);
+        let mem_bytes = self
+            .0
+            .budget
+            .get_mem_bytes_count()
+            .saturating_sub(call_trace.mem_bytes);
+        let _ = self.with_debug_budget(|| {
+            let result = match res {
+                Ok(rv) => Ok(self.trace_val(*rv)),
+                Err(e) => Err(ScStatus::try_from(e.status).unwrap_or(UNTRACEABLE_STATUS)),
+            };
+            self.get_events_mut(|events| {
+                events.record(
+                    InternalEvent::Trace(InternalTraceEvent {
+                        event: TraceEvent::Return {
+                            contract_id: call_trace.contract_id,
+                            function: call_trace.function,
+                            result,
+                            cpu_insns,
+                            mem_bytes,
+                        },
+                        in_successful_contract_call: true,
+                    }),
+                    self.as_budget(),
+                )
+            })
+        });
+    }
+
+    // Converts a traced value to XDR, falling back to `UNTRACEABLE_STATUS`.
+    fn trace_val(&self, val: RawVal) -> ScVal {
+        self.from_host_val(val)
+            .unwrap_or(ScVal::Status(UNTRACEABLE_STATUS))
+    }
+
     /// Pushes a test contract [`Frame`], runs a closure, and then pops the
     /// frame, rolling back if the closure returned an error. Returns the result
     /// that the closure returned (or any error caused during the frame
@@ -600,6 +994,30 @@ impl Host {
         self.0.events.borrow().externalize(&self)
     }
 
+    /// Writes the events recorded so far to the `debug` log, rendered with
+    /// [`Host::render_event`].
+    pub fn dump_events_to_debug_log(&self) {
+        use log::debug;
+        // The events are copied out first, as converting them may record
+        // debug events of its own.
+        let events: Vec<InternalEvent> = self.0.events.borrow().iter().cloned().collect();
+        debug!("=======Start of events=======");
+        for e in events.iter() {
+            // The conversion is charged to the shadow budget, so dumping the
+            // events never affects the metering of the contract.
+            let mut he = None;
+            let _ = self.with_debug_budget(|| {
+                he = Some(e.to_host_event(self)?);
+                Ok(())
+            });
+            match he {
+                Some(he) => debug!("{}", self.render_event(&he)),
+                None => debug!("{:?}", e),
+            }
+        }
+        debug!("========End of events========")
+    }
+
     // Notes on metering: free
     #[cfg(feature = "vm")]
     fn decode_vmslice(&self, pos: RawVal, len: RawVal) -> Result<VmSlice, HostError> {
@@ -839,6 +1257,21 @@ impl Host {
     }
 
     // Notes on metering: this is covered by the called components.
+    // Returns whether there is a contract to call with the given id, i.e. its
+    // source is in the storage or (in tests) it is a registered native
+    // contract.
+    pub(crate) fn contract_exists(&self, id: &Hash) -> Result<bool, HostError> {
+        #[cfg(any(test, feature = "testutils"))]
+        if self.0.contracts.borrow().contains_key(id) {
+            return Ok(true);
+        }
+        let storage_key = self.contract_source_ledger_key(id.metered_clone(&self.0.budget)?);
+        self.0
+            .storage
+            .borrow_mut()
+            .has(&storage_key, self.as_budget())
+    }
+
     fn call_contract_fn(
         &self,
         id: &Hash,
@@ -1154,6 +1587,19 @@ impl Host {
             .map_err(|_| self.err_general("Failed ED25519 verification"))
     }
 
+    pub(crate) fn verify_sig_ecdsa_secp256r1_internal(
+        &self,
+        payload: &[u8],
+        public_key: &p256::ecdsa::VerifyingKey,
+        sig: &p256::ecdsa::Signature,
+    ) -> Result<(), HostError> {
+        use p256::ecdsa::signature::Verifier;
+        self.charge_budget(CostType::VerifyEcdsaSecp256r1Sig, payload.len() as u64)?;
+        public_key
+            .verify(payload, sig)
+            .map_err(|_| self.err_general("Failed ECDSA secp256r1 verification"))
+    }
+
     pub(crate) fn get_invoking_contract_internal(&self) -> Result<Hash, HostError> {
         let frames = self.0.context.borrow();
         // the previous frame must exist and must be a contract
@@ -1171,6 +1617,139 @@ impl Host {
         Ok(hash)
     }
 
+    // Like `get_invoking_contract_internal`, but returns `None` instead of
+    // failing when the current contract hasn't been invoked by a contract.
+    pub(crate) fn get_invoking_contract_opt_internal(&self) -> Result<Option<Hash>, HostError> {
+        let frames = self.0.context.borrow();
+        Ok(match frames.as_slice() {
+            [.., f2, _] => match f2 {
+                #[cfg(feature = "vm")]
+                Frame::ContractVM(vm, _, _) => Some(vm.contract_id.metered_clone(&self.0.budget)?),
+                Frame::HostFunction(_) => None,
+                Frame::Token(id, _, _) => Some(id.clone()),
+                #[cfg(any(test, feature = "testutils"))]
+                Frame::TestContract(tc) => Some(tc.id.clone()), // no metering
+            },
+            _ => None,
+        })
+    }
+
+    // Returns the id of the currently running contract, if any. Unlike
+    // `get_current_contract_id_internal` this doesn't fail outside of the
+    // contract frames (or while the context is being modified), which makes
+    // it suitable for attributing the diagnostics.
+    pub(crate) fn get_current_contract_id_opt_internal(&self) -> Result<Option<Hash>, HostError> {
+        let frames = match self.0.context.try_borrow() {
+            Ok(frames) => frames,
+            Err(_) => return Ok(None),
+        };
+        Ok(match frames.last() {
+            #[cfg(feature = "vm")]
+            Some(Frame::ContractVM(vm, _, _)) => {
+                Some(vm.contract_id.metered_clone(&self.0.budget)?)
+            }
+            Some(Frame::Token(id, _, _)) => Some(id.metered_clone(&self.0.budget)?),
+            #[cfg(any(test, feature = "testutils"))]
+            Some(Frame::TestContract(tc)) => Some(tc.id.clone()),
+            Some(Frame::HostFunction(_)) | None => None,
+        })
+    }
+
+    pub(crate) fn context_depth(&self) -> usize {
+        self.0.context.borrow().len()
+    }
+
+    // Makes `frame` available to `get_auth_policy_context` while `f` (that
+    // calls `check_auth` of the account contract) runs.
+    pub(crate) fn with_check_auth_frame<T, F>(
+        &self,
+        frame: CheckAuthFrame,
+        f: F,
+    ) -> Result<T, HostError>
+    where
+        F: FnOnce() -> Result<T, HostError>,
+    {
+        self.0.check_auth_frames.borrow_mut().push(frame);
+        let res = f();
+        self.0.check_auth_frames.borrow_mut().pop();
+        res
+    }
+
+    // Returns the frame of the `check_auth` call that is currently running in
+    // the current contract, if any.
+    pub(crate) fn current_check_auth_frame(&self) -> Option<CheckAuthFrame> {
+        let depth = self.context_depth();
+        self.0
+            .check_auth_frames
+            .borrow()
+            .last()
+            .filter(|frame| {
+                frame.depth == depth
+                    && matches!(
+                        self.get_current_contract_id_opt_internal(),
+                        Ok(Some(id)) if id == frame.account_contract
+                    )
+            })
+            .cloned()
+    }
+
+    // Requires `address` to have authorized the current contract invocation
+    // with all of its arguments. Classic accounts have to be authenticated
+    // with the signer weight of at least the `threshold` level of the account.
+    pub(crate) fn require_auth_internal(
+        &self,
+        address: Object,
+        threshold: ThresholdIndexes,
+    ) -> Result<(), HostError> {
+        let addr = self.visit_obj(address, |addr: &ScAddress| Ok(addr.clone()))?;
+        let args = self.with_current_frame(|f| {
+            let args = match f {
+                #[cfg(feature = "vm")]
+                Frame::ContractVM(_, _, args) => args,
+                Frame::HostFunction(_) => {
+                    return Err(self.err_general("require_auth is not suppported for host fns"))
+                }
+                Frame::Token(_, _, args) => args,
+                #[cfg(any(test, feature = "testutils"))]
+                Frame::TestContract(c) => &c.args,
+            };
+            Ok(self.rawvals_to_scvec(args.iter())?)
+        })?;
+
+        self.0.authorization_manager.borrow_mut().require_auth(
+            self,
+            address.get_handle(),
+            addr,
+            args,
+            threshold,
+        )
+    }
+
+    /// Returns the authorization tree of every address that has been recorded
+    /// (in the recording mode) or enforced (in the enforcing mode) during the
+    /// last contract invocation. See [crate::auth::export] for rendering the
+    /// trees as JSON or DOT.
+    pub fn get_authorization_trees(&self) -> Result<Vec<AuthorizationTree>, HostError> {
+        #[cfg(not(any(test, feature = "testutils")))]
+        {
+            Ok(self
+                .0
+                .authorization_manager
+                .borrow()
+                .get_authorization_trees())
+        }
+        #[cfg(any(test, feature = "testutils"))]
+        {
+            Ok(self
+                .0
+                .previous_authorization_manager
+                .borrow()
+                .as_ref()
+                .ok_or(self.err_general("previous invocation is missing - no auth data to get"))?
+                .get_authorization_trees())
+        }
+    }
+
     // Returns the recorded per-address authorization payloads that would cover the
     // top-level contract function invocation in the enforcing mode.
     // This should only be called in the recording authorization mode, i.e. only
@@ -1363,6 +1942,22 @@ impl VmCallerEnv for Host {
         Ok(RawVal::from_void())
     }
 
+    // Notes on metering: covered by the components
+    fn log(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        level: RawVal,
+        target: Symbol,
+        values: Object,
+    ) -> Result<RawVal, HostError> {
+        let level =
+            LogLevel::from_u32(self.u32_from_rawval_input("level", level)?).ok_or_else(|| {
+                self.err_status_msg(ScHostFnErrorCode::InputArgsInvalid, "invalid log level")
+            })?;
+        self.record_log_event(level, target, values)?;
+        Ok(RawVal::from_void())
+    }
+
     fn log_fmt_values(
         &self,
         _vmcaller: &mut VmCaller<Host>,
@@ -1370,13 +1965,17 @@ impl VmCallerEnv for Host {
         args: Object,
     ) -> Result<RawVal, HostError> {
         if cfg!(feature = "hostfn_log_fmt_values") {
-            let fmt: String = self
-                .visit_obj(fmt, move |hv: &Vec<u8>| Ok(String::from_utf8(hv.clone())))?
-                .map_err(|_| {
-                    self.err_general("log_fmt_values fmt string contains is invalid utf8")
-                })?;
-            let args: HostVec = self.visit_obj(args, move |hv: &HostVec| Ok(hv.clone()))?;
-            self.record_debug_event(DebugEvent::new().msg(fmt).args(args.iter().cloned()))?;
+            // Formatting is diagnostic-only work, so it is metered against the
+            // shadow budget along with the debug event itself.
+            self.with_debug_budget(|| {
+                let fmt: String = self
+                    .visit_obj(fmt, move |hv: &Vec<u8>| Ok(String::from_utf8(hv.clone())))?
+                    .map_err(|_| {
+                        self.err_general("log_fmt_values fmt string contains is invalid utf8")
+                    })?;
+                let args: HostVec = self.visit_obj(args, move |hv: &HostVec| Ok(hv.clone()))?;
+                self.record_debug_event(DebugEvent::new().msg(fmt).args(args.iter().cloned()))
+            })?;
         }
         Ok(RawVal::from_void())
     }
@@ -2253,6 +2852,21 @@ impl VmCallerEnv for Host {
         Ok(res?.into())
     }
 
+    fn verify_sig_ecdsa_secp256r1(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        x: Object,
+        k: Object,
+        s: Object,
+    ) -> Result<RawVal, HostError> {
+        let public_key = self.secp256r1_pub_key_from_obj_input(k)?;
+        let sig = self.secp256r1_signature_from_obj_input(s)?;
+        let res = self.visit_obj(x, |payload: &Vec<u8>| {
+            self.verify_sig_ecdsa_secp256r1_internal(payload, &public_key, &sig)
+        });
+        Ok(res?.into())
+    }
+
     fn get_ledger_version(&self, _vmcaller: &mut VmCaller<Host>) -> Result<RawVal, Self::Error> {
         self.with_ledger_info(|li| Ok(li.protocol_version.into()))
     }
@@ -2334,6 +2948,7 @@ impl VmCallerEnv for Host {
                 address.get_handle(),
                 addr,
                 self.call_args_to_scvec(args)?,
+                ThresholdIndexes::Med,
             )?
             .into())
     }
@@ -2343,29 +2958,27 @@ impl VmCallerEnv for Host {
         vmcaller: &mut VmCaller<Self::VmUserState>,
         address: Object,
     ) -> Result<RawVal, Self::Error> {
-        let addr = self.visit_obj(address, |addr: &ScAddress| Ok(addr.clone()))?;
-        let args = self.with_current_frame(|f| {
-            let args = match f {
-                #[cfg(feature = "vm")]
-                Frame::ContractVM(_, _, args) => args,
-                Frame::HostFunction(_) => {
-                    return Err(self.err_general("require_auth is not suppported for host fns"))
-                }
-                Frame::Token(_, _, args) => args,
-                #[cfg(any(test, feature = "testutils"))]
-                Frame::TestContract(c) => &c.args,
-            };
-            Ok(self.rawvals_to_scvec(args.iter())?)
-        })?;
-
         Ok(self
-            .0
-            .authorization_manager
-            .borrow_mut()
-            .require_auth(self, address.get_handle(), addr, args)?
+            .require_auth_internal(address, ThresholdIndexes::Med)?
             .into())
     }
 
+    fn authorize_as_curr_contract(
+        &self,
+        vmcaller: &mut VmCaller<Self::VmUserState>,
+        auth_entries: Object,
+    ) -> Result<RawVal, Self::Error> {
+        self.authorize_as_curr_contract_internal(auth_entries.into())?;
+        Ok(RawVal::from_void())
+    }
+
+    fn get_auth_policy_context(
+        &self,
+        vmcaller: &mut VmCaller<Self::VmUserState>,
+    ) -> Result<Object, Self::Error> {
+        self.get_auth_policy_context_internal()
+    }
+
     fn get_current_contract_id(
         &self,
         vmcaller: &mut VmCaller<Self::VmUserState>,
@@ -170,6 +170,44 @@ impl Host {
         self.visit_obj(k, |bytes: &Vec<u8>| self.ed25519_pub_key_from_bytes(bytes))
     }
 
+    pub(crate) fn secp256r1_pub_key_from_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<p256::ecdsa::VerifyingKey, HostError> {
+        self.charge_budget(CostType::ComputeEcdsaSecp256r1PubKey, bytes.len() as u64)?;
+        p256::ecdsa::VerifyingKey::from_sec1_bytes(bytes).map_err(|_| {
+            self.err_status_msg(ScHostObjErrorCode::UnexpectedType, "invalid public key")
+        })
+    }
+
+    pub(crate) fn secp256r1_pub_key_from_obj_input(
+        &self,
+        k: Object,
+    ) -> Result<p256::ecdsa::VerifyingKey, HostError> {
+        self.visit_obj(k, |bytes: &Vec<u8>| {
+            self.secp256r1_pub_key_from_bytes(bytes)
+        })
+    }
+
+    pub(crate) fn secp256r1_signature_from_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<p256::ecdsa::Signature, HostError> {
+        let arr: [u8; 64] = self.fixed_length_bytes_from_slice("sig", bytes)?;
+        p256::ecdsa::Signature::try_from(arr.as_slice()).map_err(|_| {
+            self.err_status_msg(ScHostObjErrorCode::UnexpectedType, "invalid signature")
+        })
+    }
+
+    pub(crate) fn secp256r1_signature_from_obj_input(
+        &self,
+        sig: Object,
+    ) -> Result<p256::ecdsa::Signature, HostError> {
+        self.visit_obj(sig, |bytes: &Vec<u8>| {
+            self.secp256r1_signature_from_bytes(bytes)
+        })
+    }
+
     pub(crate) fn account_id_from_bytes(&self, k: Object) -> Result<AccountId, HostError> {
         self.visit_obj(k, |bytes: &Vec<u8>| {
             Ok(AccountId(xdr::PublicKey::PublicKeyTypeEd25519(
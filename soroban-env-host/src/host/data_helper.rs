@@ -1,15 +1,16 @@
-use core::cmp::min;
+use std::rc::Rc;
 
 use soroban_env_common::{Env, InvokerType};
 
 use crate::budget::AsBudget;
+use crate::native_contract::account_contract::AccountSigners;
 use crate::xdr::{
     AccountEntry, AccountId, Asset, ContractCodeEntry, ContractDataEntry, Hash, HashIdPreimage,
     HashIdPreimageContractId, HashIdPreimageCreateContractArgs, HashIdPreimageEd25519ContractId,
     HashIdPreimageFromAsset, HashIdPreimageSourceAccountContractId, LedgerEntry, LedgerEntryData,
     LedgerEntryExt, LedgerKey, LedgerKeyAccount, LedgerKeyContractCode, LedgerKeyContractData,
-    LedgerKeyTrustLine, PublicKey, ScContractCode, ScHostStorageErrorCode, ScHostValErrorCode,
-    ScObject, ScStatic, ScVal, Signer, SignerKey, ThresholdIndexes, TrustLineAsset, Uint256,
+    LedgerKeyTrustLine, ScContractCode, ScHostStorageErrorCode, ScHostValErrorCode, ScObject,
+    ScStatic, ScVal, TrustLineAsset, Uint256,
 };
 use crate::{Host, HostError};
 
@@ -173,6 +174,30 @@ impl Host {
         })
     }
 
+    // Loads the thresholds and signers of the account needed to authenticate
+    // it. These are cached until the end of the current invocation.
+    pub(crate) fn load_account_signers(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Rc<AccountSigners>, HostError> {
+        if let Some((_, signers)) = self
+            .0
+            .account_signers
+            .borrow()
+            .iter()
+            .find(|(id, _)| id == account_id)
+        {
+            return Ok(Rc::clone(signers));
+        }
+        let account = self.load_account(account_id.metered_clone(self.as_budget())?)?;
+        let signers = Rc::new(AccountSigners::from_account(self, &account)?);
+        self.0.account_signers.borrow_mut().push((
+            account_id.metered_clone(self.as_budget())?,
+            Rc::clone(&signers),
+        ));
+        Ok(signers)
+    }
+
     pub(crate) fn to_account_key(&self, account_id: AccountId) -> LedgerKey {
         LedgerKey::Account(LedgerKeyAccount { account_id })
     }
@@ -205,42 +230,6 @@ impl Host {
         LedgerKey::Trustline(LedgerKeyTrustLine { account_id, asset })
     }
 
-    pub(crate) fn get_signer_weight_from_account(
-        &self,
-        target_signer: Uint256,
-        account: &AccountEntry,
-    ) -> Result<u8, HostError> {
-        if account.account_id
-            == AccountId(PublicKey::PublicKeyTypeEd25519(
-                target_signer.metered_clone(&self.0.budget)?,
-            ))
-        {
-            // Target signer is the master key, so return the master weight
-            let threshold = account.thresholds.0[ThresholdIndexes::MasterWeight as usize];
-            Ok(threshold)
-        } else {
-            // Target signer is not the master key, so search the account signers
-            let signers: &Vec<Signer> = account.signers.as_ref();
-            for signer in signers {
-                if let SignerKey::Ed25519(ref this_signer) = signer.key {
-                    if &target_signer == this_signer {
-                        // Clamp the weight at 255. Stellar protocol before v10
-                        // allowed weights to exceed 255, but the max threshold
-                        // is 255, hence there is no point in having a larger
-                        // weight.
-                        let weight = min(signer.weight, u8::MAX as u32);
-                        // We've found the target signer in the account signers, so return the weight
-                        return Ok(weight
-                            .try_into()
-                            .map_err(|_| self.err_general("signer weight overflow"))?);
-                    }
-                }
-            }
-            // We didn't find the target signer, return 0 weight to indicate that.
-            Ok(0u8)
-        }
-    }
-
     pub(crate) fn ledger_entry_from_data(data: LedgerEntryData) -> LedgerEntry {
         LedgerEntry {
             // This is modified to the appropriate value on the core side during
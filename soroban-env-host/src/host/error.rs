@@ -1,5 +1,5 @@
 use crate::{
-    events::{Events, HostEvent},
+    events::{Event, Events},
     xdr::{self, ScStatus},
     Status,
 };
@@ -65,8 +65,8 @@ impl Debug for HostError {
                 for (i, e) in
                     ev.0.iter()
                         .rev()
-                        .filter_map(|ev| match ev {
-                            HostEvent::Debug(e) => Some(format!("{:}", e)),
+                        .filter_map(|ev| match &ev.event {
+                            Event::Debug(_) | Event::Diagnostic(_) => Some(format!("{:}", ev)),
                             _ => None,
                         })
                         .take(MAX_DEBUG_EVENTS)
@@ -91,6 +91,13 @@ impl Debug for HostError {
 }
 
 impl HostError {
+    /// Returns `true` if this error is the result of exhausting the CPU or
+    /// memory budget.
+    pub fn is_budget_exceeded(&self) -> bool {
+        self.status == Status::from(xdr::ScVmErrorCode::TrapCpuLimitExceeded)
+            || self.status == Status::from(xdr::ScVmErrorCode::TrapMemLimitExceeded)
+    }
+
     #[cfg(test)]
     pub fn result_matches_err_status<T, C>(res: Result<T, HostError>, code: C) -> bool
     where
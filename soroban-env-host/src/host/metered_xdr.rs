@@ -62,8 +62,12 @@ impl Host {
     pub(crate) fn metered_hash_xdr(&self, obj: &impl WriteXdr) -> Result<[u8; 32], HostError> {
         let mut buf = vec![];
         self.metered_write_xdr(obj, &mut buf)?;
-        self.charge_budget(CostType::ComputeSha256Hash, buf.len() as u64)?;
-        Ok(Sha256::digest(buf).try_into()?)
+        self.metered_sha256(&buf)
+    }
+
+    pub(crate) fn metered_sha256(&self, bytes: &[u8]) -> Result<[u8; 32], HostError> {
+        self.charge_budget(CostType::ComputeSha256Hash, bytes.len() as u64)?;
+        Ok(Sha256::digest(bytes).into())
     }
 
     pub(crate) fn metered_from_xdr<T: ReadXdr>(&self, bytes: &[u8]) -> Result<T, HostError> {
@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use crate::events::{DebugError, CONTRACT_EVENT_TOPICS_LIMIT, TOPIC_BYTES_LENGTH_LIMIT};
+use crate::events::{ContractEventLimitViolation, ContractEventLimits, DebugError};
 use crate::xdr::{ScHostFnErrorCode, ScHostObjErrorCode};
 use crate::{host_object::HostObject, Host, HostError, Object, RawVal, Tag};
 
@@ -81,62 +81,186 @@ impl Host {
         self.valid_range_from_start_end_bound(start, end, bound)
     }
 
+    fn event_limit_err(
+        &self,
+        violation: ContractEventLimitViolation,
+        msg: &'static str,
+        actual: usize,
+        limit: usize,
+    ) -> HostError {
+        self.err(
+            DebugError::new(violation.status())
+                .msg(msg)
+                .arg(violation.name())
+                .arg(RawVal::from_u32(u32::try_from(actual).unwrap_or(u32::MAX)))
+                .arg(RawVal::from_u32(u32::try_from(limit).unwrap_or(u32::MAX))),
+        )
+    }
+
+    // Checks a value (and everything nested in it) recorded as a part of a
+    // contract event against the limits. `depth` is the number of the
+    // containers the value is nested in. Topics are additionally restricted
+    // in the types and the `Bytes` length.
     // Metering: covered by components
-    // TODO: the validation is incomplete. Need to further restrict Map, Vec sizes.
-    fn validate_topic(&self, topic: RawVal) -> Result<(), HostError> {
-        if topic.is_u63() {
+    fn validate_event_value(
+        &self,
+        val: RawVal,
+        is_topic: bool,
+        depth: u32,
+        limits: &ContractEventLimits,
+    ) -> Result<(), HostError> {
+        if val.is_u63() || val.get_tag() != Tag::Object {
+            return Ok(());
+        }
+        let check_container = |len: usize| -> Result<(), HostError> {
+            if depth >= limits.max_container_depth {
+                return Err(self.event_limit_err(
+                    ContractEventLimitViolation::ContainerTooDeep,
+                    "{}: event container depth {} exceeds the limit {}",
+                    depth as usize + 1,
+                    limits.max_container_depth as usize,
+                ));
+            }
+            if len > limits.max_container_length {
+                return Err(self.event_limit_err(
+                    ContractEventLimitViolation::ContainerTooLong,
+                    "{}: event container length {} exceeds the limit {}",
+                    len,
+                    limits.max_container_length,
+                ));
+            }
             Ok(())
-        } else {
-            match topic.get_tag() {
-                Tag::Object => {
-                    unsafe {
-                        self.unchecked_visit_val_obj(topic, |ob| {
-                            match ob {
-                                None => Err(self.err_status(ScHostObjErrorCode::UnknownReference)),
-                                Some(ho) => match ho {
-                                    HostObject::ContractCode(_) => {
-                                        Err(self.err_status(ScHostObjErrorCode::UnexpectedType))
-                                    }
-                                    HostObject::Bytes(b) => {
-                                        if b.len() > TOPIC_BYTES_LENGTH_LIMIT {
-                                            // TODO: use more event-specific error codes than `UnexpectedType`.
-                                            // Something like "topic bytes exceeds length limit"
-                                            Err(self.err_status(ScHostObjErrorCode::UnexpectedType))
-                                        } else {
-                                            Ok(())
-                                        }
-                                    }
-                                    _ => Ok(()),
-                                },
-                            }
-                        })
+        };
+        unsafe {
+            self.unchecked_visit_val_obj(val, |ob| match ob {
+                None => Err(self.err_status(ScHostObjErrorCode::UnknownReference)),
+                Some(HostObject::ContractCode(_)) if is_topic => Err(self.err(
+                    DebugError::new(ContractEventLimitViolation::TopicTypeNotAllowed.status())
+                        .msg("{}: contract code is not allowed in event topics")
+                        .arg(ContractEventLimitViolation::TopicTypeNotAllowed.name()),
+                )),
+                Some(HostObject::Bytes(b))
+                    if is_topic && b.len() > limits.max_topic_bytes_length =>
+                {
+                    Err(self.event_limit_err(
+                        ContractEventLimitViolation::TopicBytesTooLong,
+                        "{}: topic bytes length {} exceeds the limit {}",
+                        b.len(),
+                        limits.max_topic_bytes_length,
+                    ))
+                }
+                Some(HostObject::Vec(vv)) => {
+                    check_container(vv.len())?;
+                    for e in vv.iter() {
+                        self.validate_event_value(*e, is_topic, depth + 1, limits)?;
                     }
+                    Ok(())
                 }
-                _ => Ok(()),
-            }
+                Some(HostObject::Map(mm)) => {
+                    check_container(mm.len())?;
+                    for (k, v) in mm.iter(self)? {
+                        self.validate_event_value(*k, is_topic, depth + 1, limits)?;
+                        self.validate_event_value(*v, is_topic, depth + 1, limits)?;
+                    }
+                    Ok(())
+                }
+                Some(_) => Ok(()),
+            })
         }
     }
 
-    pub(crate) fn validate_contract_event_topics(&self, topics: Object) -> Result<(), HostError> {
+    // Metering: covered by components
+    fn serialized_event_value_size(&self, val: RawVal) -> Result<usize, HostError> {
+        let scv = self.from_host_val(val)?;
+        let mut buf = Vec::<u8>::new();
+        self.metered_write_xdr(&scv, &mut buf)?;
+        Ok(buf.len())
+    }
+
+    // Returns the serialized size of the topics.
+    // Metering: covered by components
+    pub(crate) fn validate_contract_event_topics(
+        &self,
+        topics: Object,
+    ) -> Result<usize, HostError> {
+        let limits = self.0.event_limits.borrow().clone();
         unsafe {
-            self.unchecked_visit_val_obj(topics.into(), |ob| {
-                match ob {
-                    None => Err(self.err_status(ScHostObjErrorCode::UnknownReference)),
-                    Some(ho) => match ho {
-                        HostObject::Vec(vv) => {
-                            if vv.len() > CONTRACT_EVENT_TOPICS_LIMIT {
-                                // TODO: proper error code "event topics exceeds count limit"
-                                return Err(self.err_status(ScHostObjErrorCode::UnknownError));
-                            }
-                            for &topic in vv.iter() {
-                                self.validate_topic(topic)?;
-                            }
-                            Ok(())
-                        }
-                        _ => Err(self.err_status(ScHostObjErrorCode::UnexpectedType)),
-                    },
+            self.unchecked_visit_val_obj(topics.into(), |ob| match ob {
+                None => Err(self.err_status(ScHostObjErrorCode::UnknownReference)),
+                Some(HostObject::Vec(vv)) => {
+                    if vv.len() > limits.max_topics {
+                        return Err(self.event_limit_err(
+                            ContractEventLimitViolation::TooManyTopics,
+                            "{}: event topics count {} exceeds the limit {}",
+                            vv.len(),
+                            limits.max_topics,
+                        ));
+                    }
+                    for topic in vv.iter() {
+                        self.validate_event_value(*topic, true, 0, &limits)?;
+                    }
+                    Ok(())
                 }
-            })
+                Some(_) => Err(self.err_status(ScHostObjErrorCode::UnexpectedType)),
+            })?;
+        }
+        let size = self.serialized_event_value_size(topics.into())?;
+        if size > limits.max_topics_size {
+            return Err(self.event_limit_err(
+                ContractEventLimitViolation::TopicsTooLarge,
+                "{}: event topics size {} exceeds the limit {}",
+                size,
+                limits.max_topics_size,
+            ));
+        }
+        Ok(size)
+    }
+
+    // Returns the serialized size of the data.
+    // Metering: covered by components
+    pub(crate) fn validate_contract_event_data(&self, data: RawVal) -> Result<usize, HostError> {
+        let limits = self.0.event_limits.borrow().clone();
+        self.validate_event_value(data, false, 0, &limits)?;
+        let size = self.serialized_event_value_size(data)?;
+        if size > limits.max_data_size {
+            return Err(self.event_limit_err(
+                ContractEventLimitViolation::DataTooLarge,
+                "{}: event data size {} exceeds the limit {}",
+                size,
+                limits.max_data_size,
+            ));
         }
+        Ok(size)
+    }
+
+    // Checks that recording one more event of the given size doesn't exceed
+    // the per-invocation limits.
+    // Metering: free
+    pub(crate) fn validate_invocation_events_usage(&self, size: usize) -> Result<(), HostError> {
+        let limits = self.0.event_limits.borrow().clone();
+        let (count, total_size) = {
+            let events = self.0.events.borrow();
+            (
+                events.invocation_events_count + 1,
+                events.invocation_events_size.saturating_add(size),
+            )
+        };
+        if count > limits.max_invocation_events_count {
+            return Err(self.event_limit_err(
+                ContractEventLimitViolation::TooManyInvocationEvents,
+                "{}: invocation events count {} exceeds the limit {}",
+                count,
+                limits.max_invocation_events_count,
+            ));
+        }
+        if total_size > limits.max_invocation_events_size {
+            return Err(self.event_limit_err(
+                ContractEventLimitViolation::InvocationEventsTooLarge,
+                "{}: invocation events size {} exceeds the limit {}",
+                total_size,
+                limits.max_invocation_events_size,
+            ));
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,50 @@
+//! Minimal JSON writing shared by the JSON exports of the host: the budget
+//! profiles, the authorization trees and the contract event specs. Every
+//! string written goes through [write_string], so the output is valid JSON no
+//! matter what the strings contain.
+
+use std::fmt::Write;
+
+// Appends `s` as a JSON string, escaping the characters that can't appear in
+// it as is.
+pub(crate) fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// Appends `s` as a JSON string, or `null` if there is none.
+pub(crate) fn write_opt_string(out: &mut String, s: Option<&str>) {
+    match s {
+        Some(s) => write_string(out, s),
+        None => out.push_str("null"),
+    }
+}
+
+// Appends a JSON array of `items`, each one written by `write_item`.
+pub(crate) fn write_array<T>(
+    out: &mut String,
+    items: impl IntoIterator<Item = T>,
+    mut write_item: impl FnMut(&mut String, T),
+) {
+    out.push('[');
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_item(out, item);
+    }
+    out.push(']');
+}
@@ -30,8 +30,10 @@
 
 pub mod budget;
 pub mod events;
+pub mod fees;
 mod host;
 pub(crate) mod host_object;
+mod json;
 
 mod native_contract;
 
@@ -3,22 +3,23 @@ use crate::auth::AuthorizedInvocation;
 // it doesn't need to be directly invoked. But semantically this is analagous
 // to a generic smart wallet contract that supports authentication and blanket
 // context authorization.
+use crate::budget::CostType;
 use crate::host::metered_clone::MeteredClone;
 use crate::host::Host;
 use crate::native_contract::{
-    base_types::{BytesN, Map},
+    base_types::{Address, Bytes, BytesN, Map},
     contract_error::ContractError,
 };
 use crate::{err, HostError};
-use core::cmp::Ordering;
-use soroban_env_common::xdr::{Hash, ThresholdIndexes, Uint256};
-use soroban_env_common::{Env, EnvBase, RawVal, Symbol, TryFromVal, TryIntoVal};
+use sha2::{Digest, Sha256};
+use soroban_env_common::xdr::{Hash, ScHostStorageErrorCode, SignerKey, ThresholdIndexes, Uint256};
+use soroban_env_common::{Env, EnvBase, Object, RawVal, Symbol, TryFromVal, TryIntoVal};
 
 use crate::native_contract::base_types::Vec as HostVec;
 
 const MAX_ACCOUNT_SIGNATURES: u32 = 20;
 
-use soroban_env_common::xdr::AccountId;
+use soroban_env_common::xdr::{AccountEntry, AccountId, PublicKey, ScAddress};
 use soroban_native_sdk_macros::contracttype;
 
 #[derive(Clone)]
@@ -29,6 +30,18 @@ pub struct AuthorizationContext {
     pub args: HostVec,
 }
 
+// A node of the invocation tree authorized by a contract on its own behalf via
+// `authorize_as_curr_contract`. `sub_invocs` contains the nested
+// `InvokerContractAuthEntry` values.
+#[derive(Clone)]
+#[contracttype]
+pub struct InvokerContractAuthEntry {
+    pub contract: BytesN<32>,
+    pub fn_name: Symbol,
+    pub args: HostVec,
+    pub sub_invocs: HostVec,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct AccountEd25519Signature {
@@ -36,6 +49,101 @@ pub struct AccountEd25519Signature {
     pub signature: BytesN<64>,
 }
 
+// Signature made by a WebAuthn authenticator (e.g. a passkey) with a
+// secp256r1 key, given in the compressed SEC1 form. The authenticator signs
+// `auth_data || sha256(client)`, where `client` is the client data JSON
+// containing the signature payload as the challenge.
+#[derive(Clone)]
+#[contracttype]
+pub struct AccountWebAuthnSignature {
+    pub public_key: BytesN<33>,
+    pub auth_data: Bytes,
+    pub client: Bytes,
+    pub signature: BytesN<64>,
+}
+
+// One of the signatures passed to `check_account_authentication`. The
+// signature kind is told apart by the fields of the signature map.
+enum AccountSignature {
+    Ed25519(AccountEd25519Signature),
+    WebAuthn(AccountWebAuthnSignature),
+}
+
+impl AccountSignature {
+    fn from_val(host: &Host, val: RawVal) -> Result<Self, HostError> {
+        let obj: Object = val.try_into().map_err(|_| {
+            host.err_status_msg(
+                ContractError::AuthenticationError,
+                "incompatible signature format",
+            )
+        })?;
+        let is_webauthn: bool = host
+            .map_has(obj, Symbol::from_str("auth_data").into())?
+            .try_into()?;
+        if is_webauthn {
+            Ok(Self::WebAuthn(obj.try_into_val(host)?))
+        } else {
+            Ok(Self::Ed25519(obj.try_into_val(host)?))
+        }
+    }
+}
+
+// Versioned extension of the `check_auth` arguments that allows account
+// contracts to enforce policies (like spending limits or time locks) based on
+// the details of the authorization being checked. It can be fetched via the
+// `get_auth_policy_context` host function while `check_auth` is running.
+// New versions may be added as new variants.
+#[derive(Clone)]
+#[contracttype]
+pub enum AuthPolicyContext {
+    V1(AuthPolicyContextV1),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AuthPolicyContextV1 {
+    pub nonce: u64,
+    // Signature expiration ledger, or 0 if the signature doesn't expire.
+    pub sig_exp: u32,
+    // Invoker of the root authorized invocation.
+    pub invoker: Address,
+    pub network_id: BytesN<32>,
+    // Same contexts as the ones passed to `check_auth`.
+    pub contexts: HostVec,
+}
+
+// The `check_auth` call that is currently running, along with the inputs for
+// building its `AuthPolicyContext`. The context is only built on demand, as
+// most of the account contracts don't need it.
+#[derive(Clone)]
+pub(crate) struct CheckAuthFrame {
+    // Depth of the context stack while `check_auth` is running.
+    pub(crate) depth: usize,
+    pub(crate) account_contract: Hash,
+    pub(crate) nonce: u64,
+    pub(crate) signature_expiration_ledger: Option<u32>,
+    // `None` when the root invocation has been invoked directly by the
+    // transaction source account.
+    pub(crate) invoking_contract: Option<Hash>,
+    pub(crate) contexts: HostVec,
+}
+
+impl CheckAuthFrame {
+    pub(crate) fn to_policy_context(&self, host: &Host) -> Result<AuthPolicyContext, HostError> {
+        let invoker = match &self.invoking_contract {
+            Some(contract_id) => ScAddress::Contract(contract_id.metered_clone(host.budget_ref())?),
+            None => ScAddress::Account(host.source_account()?),
+        };
+        Ok(AuthPolicyContext::V1(AuthPolicyContextV1 {
+            nonce: self.nonce,
+            sig_exp: self.signature_expiration_ledger.unwrap_or(0),
+            invoker: Address::try_from_val(host, &host.add_host_object(invoker)?)?,
+            network_id: BytesN::try_from_val(host, &host.get_ledger_network_id()?)?,
+            contexts: self.contexts.clone(),
+        }))
+    }
+}
+
 impl AuthorizationContext {
     fn from_invocation(host: &Host, invocation: &AuthorizedInvocation) -> Result<Self, HostError> {
         let args =
@@ -69,34 +177,186 @@ pub(crate) fn check_account_contract_auth(
     signature_payload: &[u8; 32],
     signature_args: &Vec<RawVal>,
     invocation: &AuthorizedInvocation,
+    nonce: u64,
+    signature_expiration_ledger: Option<u32>,
 ) -> Result<(), HostError> {
+    let (frame, args) = prepare_account_contract_auth(
+        host,
+        account_contract,
+        signature_payload,
+        signature_args,
+        invocation,
+        nonce,
+        signature_expiration_ledger,
+    )?;
+    call_check_auth(host, account_contract, frame, args)
+}
+
+// Emulates `check_account_contract_auth` with signatures that are most likely
+// dummy ones: `check_auth` still has to run in order to charge for it, but it
+// is expected to reject the signatures, so any failure of `check_auth` itself
+// besides running out of budget is ignored. Preparing the call and a missing
+// account contract are still errors.
+pub(crate) fn simulate_account_contract_auth(
+    host: &Host,
+    account_contract: &Hash,
+    signature_payload: &[u8; 32],
+    signature_args: &Vec<RawVal>,
+    invocation: &AuthorizedInvocation,
+    nonce: u64,
+    signature_expiration_ledger: Option<u32>,
+) -> Result<(), HostError> {
+    let (frame, args) = prepare_account_contract_auth(
+        host,
+        account_contract,
+        signature_payload,
+        signature_args,
+        invocation,
+        nonce,
+        signature_expiration_ledger,
+    )?;
+    if !host.contract_exists(account_contract)? {
+        return Err(host.err_status_msg(
+            ScHostStorageErrorCode::AccessToUnknownEntry,
+            "account contract does not exist",
+        ));
+    }
+    match call_check_auth(host, account_contract, frame, args) {
+        Err(e) if e.is_budget_exceeded() => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// Builds the `check_auth` frame and arguments (payload, signatures and the
+// authorization contexts).
+fn prepare_account_contract_auth(
+    host: &Host,
+    account_contract: &Hash,
+    signature_payload: &[u8; 32],
+    signature_args: &Vec<RawVal>,
+    invocation: &AuthorizedInvocation,
+    nonce: u64,
+    signature_expiration_ledger: Option<u32>,
+) -> Result<(CheckAuthFrame, [RawVal; 3]), HostError> {
     let payload_obj = host.bytes_new_from_slice(signature_payload)?;
     let signature_args_vec = HostVec::try_from_val(host, signature_args)?;
     let mut auth_context_vec = HostVec::new(host)?;
     invocation_tree_to_auth_contexts(host, invocation, &mut auth_context_vec)?;
-    Ok(host
-        .call_n_internal(
-            account_contract,
-            Symbol::from_str("check_auth"),
-            &[
-                payload_obj.into(),
-                signature_args_vec.into(),
-                auth_context_vec.into(),
-            ],
-            // Allow reentry for this function in order to do wallet admin ops
-            // within the auth framework. Maybe there is a more elegant way
-            // around this.
-            // TODO: check if there are security concerns about this.
-            true,
-        )?
-        .try_into()?)
+    let frame = CheckAuthFrame {
+        depth: host.context_depth() + 1,
+        account_contract: account_contract.metered_clone(host.budget_ref())?,
+        nonce,
+        signature_expiration_ledger,
+        invoking_contract: host.get_invoking_contract_opt_internal()?,
+        contexts: auth_context_vec.clone(),
+    };
+    Ok((
+        frame,
+        [
+            payload_obj.into(),
+            signature_args_vec.into(),
+            auth_context_vec.into(),
+        ],
+    ))
+}
+
+fn call_check_auth(
+    host: &Host,
+    account_contract: &Hash,
+    frame: CheckAuthFrame,
+    args: [RawVal; 3],
+) -> Result<(), HostError> {
+    host.with_check_auth_frame(frame, || {
+        Ok(host
+            .call_n_internal(
+                account_contract,
+                Symbol::from_str("check_auth"),
+                &args,
+                // Allow reentry for this function in order to do wallet admin ops
+                // within the auth framework. Maybe there is a more elegant way
+                // around this.
+                // TODO: check if there are security concerns about this.
+                true,
+            )?
+            .try_into()?)
+    })
+}
+
+// Thresholds and signer weights of a classic account, i.e. the parts of its
+// `AccountEntry` that are needed for authentication.
+// Classic accounts can't have secp256r1 signers, so a secp256r1 key is added
+// to an account as a `PreAuthTx` signer with the SHA-256 hash of its
+// compressed SEC1 form. No transaction can have that hash, so such a signer
+// can't authorize anything else.
+pub(crate) struct AccountSigners {
+    thresholds: [u8; 4],
+    // The master key goes first.
+    signer_weights: Vec<(Uint256, u8)>,
+    secp256r1_signer_weights: Vec<(Uint256, u8)>,
+}
+
+impl AccountSigners {
+    pub(crate) fn from_account(host: &Host, account: &AccountEntry) -> Result<Self, HostError> {
+        let PublicKey::PublicKeyTypeEd25519(master_key) = &account.account_id.0;
+        let mut signer_weights = vec![(
+            master_key.metered_clone(host.budget_ref())?,
+            account.thresholds.0[ThresholdIndexes::MasterWeight as usize],
+        )];
+        let mut secp256r1_signer_weights = vec![];
+        for signer in account.signers.iter() {
+            // Clamp the weight at 255. Stellar protocol before v10
+            // allowed weights to exceed 255, but the max threshold
+            // is 255, hence there is no point in having a larger
+            // weight.
+            let weight = signer.weight.min(u8::MAX as u32) as u8;
+            match &signer.key {
+                SignerKey::Ed25519(key) => {
+                    signer_weights.push((key.metered_clone(host.budget_ref())?, weight))
+                }
+                SignerKey::PreAuthTx(key_hash) => secp256r1_signer_weights
+                    .push((key_hash.metered_clone(host.budget_ref())?, weight)),
+                _ => (),
+            }
+        }
+        Ok(Self {
+            thresholds: account.thresholds.0,
+            signer_weights,
+            secp256r1_signer_weights,
+        })
+    }
+
+    // Returns 0 weight for the keys that don't belong to the account.
+    pub(crate) fn weight(&self, key: &Uint256) -> u8 {
+        Self::find_weight(&self.signer_weights, key)
+    }
+
+    // Returns 0 weight for the key hashes that don't belong to the account.
+    pub(crate) fn secp256r1_weight(&self, key_hash: &Uint256) -> u8 {
+        Self::find_weight(&self.secp256r1_signer_weights, key_hash)
+    }
+
+    fn find_weight(signer_weights: &[(Uint256, u8)], key: &Uint256) -> u8 {
+        signer_weights
+            .iter()
+            .find(|(k, _)| k == key)
+            .map_or(0, |(_, weight)| *weight)
+    }
+
+    pub(crate) fn threshold(&self, level: ThresholdIndexes) -> u8 {
+        self.thresholds[level as usize]
+    }
 }
 
+// Authenticates the account with the given signatures of `payload`. The
+// signers must have enough weight to reach the `threshold` level of the
+// account. Every signature is either an ed25519 signature of one of the
+// account signers, or a WebAuthn signature of one of its secp256r1 signers.
 pub(crate) fn check_account_authentication(
     host: &Host,
     account_id: &AccountId,
     payload: &[u8],
     signature_args: &Vec<RawVal>,
+    threshold: ThresholdIndexes,
 ) -> Result<(), HostError> {
     if signature_args.len() != 1 {
         return Err(err!(
@@ -125,32 +385,42 @@ pub(crate) fn check_account_authentication(
         ));
     }
     let payload_obj = host.bytes_new_from_slice(payload)?;
-    let account = host.load_account(account_id.metered_clone(host.budget_ref())?)?;
-    let mut prev_pk: Option<BytesN<32>> = None;
+    let account = host.load_account_signers(account_id)?;
+    // Signers are identified by their ed25519 key or the hash of their
+    // secp256r1 key.
+    let mut prev_signer: Option<Uint256> = None;
     let mut weight = 0u32;
     for i in 0..sigs.len()? {
-        let sig: AccountEd25519Signature = sigs.get(i)?;
+        let (signer, signer_weight) = match AccountSignature::from_val(host, sigs.get(i)?)? {
+            AccountSignature::Ed25519(sig) => {
+                host.verify_sig_ed25519(
+                    payload_obj.clone(),
+                    sig.public_key.clone().into(),
+                    sig.signature.into(),
+                )?;
+                let signer = Uint256(sig.public_key.to_array()?);
+                let signer_weight = account.weight(&signer);
+                (signer, signer_weight)
+            }
+            AccountSignature::WebAuthn(sig) => {
+                check_webauthn_signature(host, payload, &sig)?;
+                let signer = secp256r1_signer_key_hash(host, &sig.public_key.to_array()?)?;
+                let signer_weight = account.secp256r1_weight(&signer);
+                (signer, signer_weight)
+            }
+        };
         // Cannot take multiple signatures from the same key
-        if let Some(prev) = prev_pk {
-            if prev.compare(&sig.public_key)? != Ordering::Less {
+        if let Some(prev) = &prev_signer {
+            if prev >= &signer {
                 return Err(err!(
                     host,
                     ContractError::AuthenticationError,
                     "public keys are not ordered: {} >= {}",
-                    prev,
-                    sig.public_key
+                    BytesN::<32>::from_slice(host, &prev.0)?,
+                    BytesN::<32>::from_slice(host, &signer.0)?
                 ));
             }
         }
-
-        host.verify_sig_ed25519(
-            payload_obj.clone(),
-            sig.public_key.clone().into(),
-            sig.signature.into(),
-        )?;
-
-        let signer_weight =
-            host.get_signer_weight_from_account(Uint256(sig.public_key.to_array()?), &account)?;
         // 0 weight indicates that signer doesn't belong to this account. Treat
         // this as an error to indicate a bug in signatures, even if another
         // signers would have enough weight.
@@ -159,15 +429,15 @@ pub(crate) fn check_account_authentication(
                 host,
                 ContractError::AuthenticationError,
                 "signer '{}' does not belong to account",
-                sig.public_key
+                BytesN::<32>::from_slice(host, &signer.0)?
             ));
         }
         // Overflow isn't possible here as
         // 255 * MAX_ACCOUNT_SIGNATURES is < u32::MAX.
         weight += signer_weight as u32;
-        prev_pk = Some(sig.public_key);
+        prev_signer = Some(signer);
     }
-    let threshold = account.thresholds.0[ThresholdIndexes::Med as usize];
+    let threshold = account.threshold(threshold);
     if weight < threshold as u32 {
         Err(err!(
             host,
@@ -180,3 +450,230 @@ pub(crate) fn check_account_authentication(
         Ok(())
     }
 }
+
+// Emulates `check_account_authentication` without having the signatures: the
+// highest-weight signers of the account are assumed to have signed until the
+// threshold is reached, and the budget is charged for verifying each of their
+// signatures.
+pub(crate) fn simulate_account_authentication(
+    host: &Host,
+    account_id: &AccountId,
+    payload: &[u8],
+    threshold: ThresholdIndexes,
+) -> Result<(), HostError> {
+    // `check_account_authentication` passes the payload to the signature
+    // verification as a bytes object, so it is allocated here as well.
+    let _payload_obj = host.bytes_new_from_slice(payload)?;
+    let account = host.load_account_signers(account_id)?;
+    let mut weights: Vec<(u32, CostType)> = account
+        .signer_weights
+        .iter()
+        .map(|(_, weight)| (*weight as u32, CostType::VerifyEd25519Sig))
+        .chain(
+            account
+                .secp256r1_signer_weights
+                .iter()
+                .map(|(_, weight)| (*weight as u32, CostType::VerifyEcdsaSecp256r1Sig)),
+        )
+        .collect();
+    weights.sort_by_key(|(weight, _)| core::cmp::Reverse(*weight));
+    let threshold = account.threshold(threshold) as u32;
+    let mut weight = 0u32;
+    // Signers with 0 weight can't sign (`check_account_authentication` fails
+    // for them), so if no signer has weight, only an empty signature set can
+    // be valid and nothing is charged. Otherwise at least one signature is
+    // charged for, even if the threshold is 0.
+    for (signer_weight, verify_cost_type) in weights
+        .into_iter()
+        .filter(|(w, _)| *w > 0)
+        .take(MAX_ACCOUNT_SIGNATURES as usize)
+    {
+        host.charge_budget(verify_cost_type, payload.len() as u64)?;
+        weight += signer_weight;
+        if weight >= threshold {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// Returns the hash identifying the secp256r1 signer with the given compressed
+// SEC1 public key among the account signers.
+fn secp256r1_signer_key_hash(host: &Host, public_key: &[u8]) -> Result<Uint256, HostError> {
+    host.charge_budget(CostType::ComputeSha256Hash, public_key.len() as u64)?;
+    Ok(Uint256(Sha256::digest(public_key).into()))
+}
+
+// WebAuthn authenticator data flag indicating that the user was present.
+const WEBAUTHN_FLAG_USER_PRESENT: u8 = 0x01;
+// Length of the fixed part of the authenticator data: RP id hash, flags and
+// the signature counter.
+const WEBAUTHN_AUTH_DATA_MIN_LEN: usize = 37;
+const WEBAUTHN_GET_TYPE: &[u8] = b"webauthn.get";
+
+// Fixed-size prefix of the WebAuthn authenticator data. Attested credential
+// data and extensions that may follow it are not needed for authentication.
+#[allow(dead_code)]
+pub(crate) struct WebAuthnAuthenticatorData {
+    pub(crate) rp_id_hash: [u8; 32],
+    pub(crate) flags: u8,
+    pub(crate) sign_count: u32,
+}
+
+pub(crate) fn parse_webauthn_authenticator_data(
+    host: &Host,
+    data: &[u8],
+) -> Result<WebAuthnAuthenticatorData, HostError> {
+    if data.len() < WEBAUTHN_AUTH_DATA_MIN_LEN {
+        return Err(err!(
+            host,
+            ContractError::AuthenticationError,
+            "authenticator data is too short: {} < {}",
+            data.len() as u32,
+            WEBAUTHN_AUTH_DATA_MIN_LEN as u32
+        ));
+    }
+    let mut rp_id_hash = [0u8; 32];
+    rp_id_hash.copy_from_slice(&data[0..32]);
+    let mut sign_count = [0u8; 4];
+    sign_count.copy_from_slice(&data[33..37]);
+    Ok(WebAuthnAuthenticatorData {
+        rp_id_hash,
+        flags: data[32],
+        sign_count: u32::from_be_bytes(sign_count),
+    })
+}
+
+// Members of the WebAuthn client data JSON needed for authentication.
+pub(crate) struct WebAuthnClientData {
+    pub(crate) type_: std::vec::Vec<u8>,
+    pub(crate) challenge: std::vec::Vec<u8>,
+}
+
+pub(crate) fn parse_webauthn_client_data(
+    host: &Host,
+    json: &[u8],
+) -> Result<WebAuthnClientData, HostError> {
+    // Every member lookup scans the JSON once.
+    host.charge_budget(CostType::BytesCmp, 2 * json.len() as u64)?;
+    let member = |key: &'static str| {
+        json_string_member(json, key).ok_or_else(|| {
+            host.err_status_msg(
+                ContractError::AuthenticationError,
+                "missing or malformed member in client data",
+            )
+        })
+    };
+    Ok(WebAuthnClientData {
+        type_: member("type")?.to_vec(),
+        challenge: member("challenge")?.to_vec(),
+    })
+}
+
+// Returns the value of the string member `key` of a JSON object. Only the
+// values without escape sequences are supported, which is sufficient for the
+// `type` and `challenge` members of the WebAuthn client data (the challenge is
+// base64url-encoded).
+fn json_string_member<'a>(json: &'a [u8], key: &str) -> Option<&'a [u8]> {
+    let pattern = format!("\"{}\"", key);
+    let skip_whitespace = |mut i: usize| {
+        while i < json.len() && json[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+    let mut start = 0;
+    while let Some(pos) = json[start..]
+        .windows(pattern.len())
+        .position(|w| w == pattern.as_bytes())
+    {
+        let key_end = start + pos + pattern.len();
+        start = key_end;
+        // The same string may also occur as a value, so keep looking until
+        // it is followed by a colon.
+        let i = skip_whitespace(key_end);
+        if json.get(i) != Some(&b':') {
+            continue;
+        }
+        let i = skip_whitespace(i + 1);
+        if json.get(i) != Some(&b'"') {
+            return None;
+        }
+        let value = &json[i + 1..];
+        let value_len = value.iter().position(|c| *c == b'"')?;
+        let value = &value[..value_len];
+        if value.contains(&b'\\') {
+            return None;
+        }
+        return Some(value);
+    }
+    None
+}
+
+// Encodes `data` as base64url without padding, the way WebAuthn encodes the
+// challenge in the client data.
+pub(crate) fn base64_url_encode(data: &[u8]) -> std::vec::Vec<u8> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = std::vec::Vec::with_capacity((data.len() * 4 + 2) / 3);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize]);
+        }
+    }
+    out
+}
+
+// Verifies that `sig` is a WebAuthn assertion of `payload`: the client data
+// must be of `webauthn.get` type and have the payload as the challenge, the
+// user must have been present, and the secp256r1 signature of
+// `auth_data || sha256(client)` must be valid.
+pub(crate) fn check_webauthn_signature(
+    host: &Host,
+    payload: &[u8],
+    sig: &AccountWebAuthnSignature,
+) -> Result<(), HostError> {
+    let mut message: std::vec::Vec<u8> = host
+        .visit_obj(sig.auth_data.clone().into(), |b: &std::vec::Vec<u8>| {
+            b.metered_clone(host.budget_ref())
+        })?;
+    let auth_data = parse_webauthn_authenticator_data(host, &message)?;
+    if auth_data.flags & WEBAUTHN_FLAG_USER_PRESENT == 0 {
+        return Err(host.err_status_msg(
+            ContractError::AuthenticationError,
+            "user presence flag is not set",
+        ));
+    }
+    let client_data_hash =
+        host.visit_obj(sig.client.clone().into(), |json: &std::vec::Vec<u8>| {
+            let client_data = parse_webauthn_client_data(host, json)?;
+            if client_data.type_.as_slice() != WEBAUTHN_GET_TYPE {
+                return Err(host.err_status_msg(
+                    ContractError::AuthenticationError,
+                    "unexpected client data type",
+                ));
+            }
+            host.charge_budget(CostType::BytesCmp, client_data.challenge.len() as u64)?;
+            if client_data.challenge != base64_url_encode(payload) {
+                return Err(host.err_status_msg(
+                    ContractError::AuthenticationError,
+                    "client data challenge doesn't match the signature payload",
+                ));
+            }
+            host.charge_budget(CostType::ComputeSha256Hash, json.len() as u64)?;
+            Ok(Sha256::digest(json))
+        })?;
+    host.charge_budget(CostType::BytesAppend, client_data_hash.len() as u64)?;
+    message.extend_from_slice(&client_data_hash);
+    host.verify_sig_ecdsa_secp256r1(
+        host.bytes_new_from_slice(&message)?,
+        sig.public_key.clone().into(),
+        sig.signature.clone().into(),
+    )?;
+    Ok(())
+}
@@ -2,7 +2,7 @@ use crate::budget::CostType;
 use crate::host::{Host, HostError};
 
 use core::cmp::Ordering;
-use soroban_env_common::xdr::{AccountId, ScAddress, ScObjectType};
+use soroban_env_common::xdr::{AccountId, ScAddress, ScObjectType, ThresholdIndexes};
 use soroban_env_common::{Compare, ConversionError, Env, EnvBase, Object, RawVal, TryFromVal};
 
 #[derive(Clone)]
@@ -467,4 +467,13 @@ impl Address {
     pub(crate) fn require_auth(&self) -> Result<(), HostError> {
         Ok(self.host.require_auth(self.object)?.try_into()?)
     }
+
+    // Like `require_auth`, but classic accounts have to be authenticated
+    // against the given threshold level instead of the medium one.
+    pub(crate) fn require_auth_with_threshold(
+        &self,
+        threshold: ThresholdIndexes,
+    ) -> Result<(), HostError> {
+        self.host.require_auth_internal(self.object, threshold)
+    }
 }
@@ -1,18 +1,18 @@
-use crate::{Host, HostError, LedgerInfo};
-use ed25519_dalek::{Keypair, Signer};
+use crate::{Host, HostError};
+use ed25519_dalek::Keypair;
 use rand::thread_rng;
 use soroban_env_common::xdr::{
-    AccountId, AddressWithNonce, AuthorizedInvocation, ContractAuth, Hash, HashIdPreimage,
-    HashIdPreimageContractAuth, PublicKey, ScAddress, ScVec, Uint256,
+    AccountId, AddressWithNonce, AuthorizedInvocation, ContractAuth, Hash, PublicKey, ScAddress,
+    ScVec, Uint256,
 };
-use soroban_env_common::{EnvBase, RawVal, TryFromVal, TryIntoVal};
+use soroban_env_common::{RawVal, TryFromVal};
 
 use crate::native_contract::base_types::BytesN;
 
 pub(crate) use crate::native_contract::base_types::Vec as HostVec;
 
-use super::account_contract::AccountEd25519Signature;
 use super::base_types::Address;
+use crate::auth::testutils::account_signature_args;
 
 impl HostVec {
     pub(crate) fn from_array(host: &Host, vals: &[RawVal]) -> Result<Self, HostError> {
@@ -117,13 +117,7 @@ impl<'a> TestSigner<'a> {
         let signature_args = match self {
             TestSigner::AccountInvoker(_) => host_vec![host],
             TestSigner::Account(account_signer) => {
-                let mut signatures = HostVec::new(&host).unwrap();
-                for key in &account_signer.signers {
-                    signatures
-                        .push(&sign_payload_for_account(host, key, payload))
-                        .unwrap();
-                }
-                host_vec![host, signatures]
+                account_signature_args(host, &account_signer.signers, payload).unwrap()
             }
             TestSigner::AccountContract(signer) => (signer.sign)(payload),
             TestSigner::ContractInvoker(_) => host_vec![host],
@@ -171,16 +165,8 @@ pub(crate) fn authorize_single_invocation_with_nonce(
     };
 
     let signature_payload = if let Some(addr_with_nonce) = &address_with_nonce {
-        let signature_payload_preimage = HashIdPreimage::ContractAuth(HashIdPreimageContractAuth {
-            network_id: host
-                .with_ledger_info(|li: &LedgerInfo| Ok(li.network_id.clone()))
-                .unwrap()
-                .try_into()
-                .unwrap(),
-            invocation: root_invocation.clone(),
-            nonce: addr_with_nonce.nonce,
-        });
-        host.metered_hash_xdr(&signature_payload_preimage).unwrap()
+        host.get_authorization_signature_payload(root_invocation.clone(), addr_with_nonce.nonce)
+            .unwrap()
     } else {
         [0; 32]
     };
@@ -217,41 +203,3 @@ pub(crate) fn authorize_single_invocation(
     };
     authorize_single_invocation_with_nonce(host, signer, contract_id, function_name, args, nonce);
 }
-
-fn sign_payload_for_account(
-    host: &Host,
-    signer: &Keypair,
-    payload: &[u8],
-) -> AccountEd25519Signature {
-    AccountEd25519Signature {
-        public_key: BytesN::<32>::try_from_val(
-            host,
-            &host
-                .bytes_new_from_slice(&signer.public.to_bytes())
-                .unwrap(),
-        )
-        .unwrap(),
-        signature: BytesN::<64>::try_from_val(
-            host,
-            &host
-                .bytes_new_from_slice(&signer.sign(payload).to_bytes())
-                .unwrap(),
-        )
-        .unwrap(),
-    }
-}
-
-#[allow(dead_code)]
-pub(crate) fn sign_payload_for_ed25519(
-    host: &Host,
-    signer: &Keypair,
-    payload: &[u8],
-) -> BytesN<64> {
-    BytesN::<64>::try_from_val(
-        host,
-        &host
-            .bytes_new_from_slice(&signer.sign(payload).to_bytes())
-            .unwrap(),
-    )
-    .unwrap()
-}
@@ -7,6 +7,8 @@ mod metadata;
 pub(crate) mod public_types;
 mod storage_types;
 
+pub(crate) use event::EVENT_SPECS;
+
 #[cfg(test)]
 pub(crate) mod test_token;
 
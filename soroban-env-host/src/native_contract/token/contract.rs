@@ -13,7 +13,7 @@ use crate::native_contract::token::metadata::{
 use crate::native_contract::token::public_types::Metadata;
 use crate::{err, HostError};
 
-use soroban_env_common::xdr::Asset;
+use soroban_env_common::xdr::{Asset, ThresholdIndexes};
 use soroban_env_common::{EnvBase, TryFromVal, TryIntoVal};
 use soroban_native_sdk_macros::contractimpl;
 
@@ -185,7 +185,12 @@ impl TokenTrait for Token {
             .checked_add(amount)
             .ok_or_else(|| e.err_status(ContractError::OverflowError))?;
         write_allowance(&e, from.clone(), spender.clone(), new_allowance)?;
-        event::incr_allow(e, from, spender, amount)?;
+        event::IncrAllow {
+            from,
+            to: spender,
+            amount,
+        }
+        .publish(e)?;
         Ok(())
     }
 
@@ -203,7 +208,12 @@ impl TokenTrait for Token {
         } else {
             write_allowance(&e, from.clone(), spender.clone(), allowance - amount)?;
         }
-        event::decr_allow(e, from, spender, amount)?;
+        event::DecrAllow {
+            from,
+            to: spender,
+            amount,
+        }
+        .publish(e)?;
         Ok(())
     }
 
@@ -227,7 +237,7 @@ impl TokenTrait for Token {
         from.require_auth()?;
         spend_balance(e, from.clone(), amount)?;
         receive_balance(e, to.clone(), amount)?;
-        event::transfer(e, from, to, amount)?;
+        event::Transfer { from, to, amount }.publish(e)?;
         Ok(())
     }
 
@@ -244,7 +254,7 @@ impl TokenTrait for Token {
         spend_allowance(e, from.clone(), spender, amount)?;
         spend_balance(e, from.clone(), amount)?;
         receive_balance(e, to.clone(), amount)?;
-        event::transfer(e, from, to, amount)?;
+        event::Transfer { from, to, amount }.publish(e)?;
         Ok(())
     }
 
@@ -254,7 +264,7 @@ impl TokenTrait for Token {
         check_non_native(e)?;
         from.require_auth()?;
         spend_balance(&e, from.clone(), amount)?;
-        event::burn(e, from, amount)?;
+        event::Burn { from, amount }.publish(e)?;
         Ok(())
     }
 
@@ -265,7 +275,7 @@ impl TokenTrait for Token {
         spender.require_auth()?;
         spend_allowance(&e, from.clone(), spender, amount)?;
         spend_balance(&e, from.clone(), amount)?;
-        event::burn(e, from, amount)?;
+        event::Burn { from, amount }.publish(e)?;
         Ok(())
     }
 
@@ -274,9 +284,14 @@ impl TokenTrait for Token {
         check_nonnegative_amount(e, amount)?;
         check_admin(e, &admin)?;
         check_clawbackable(&e, from.clone())?;
-        admin.require_auth()?;
+        admin.require_auth_with_threshold(ThresholdIndexes::High)?;
         spend_balance_no_authorization_check(e, from.clone(), amount.clone())?;
-        event::clawback(e, admin, from, amount)?;
+        event::Clawback {
+            admin,
+            from,
+            amount,
+        }
+        .publish(e)?;
         Ok(())
     }
 
@@ -285,7 +300,12 @@ impl TokenTrait for Token {
         check_admin(e, &admin)?;
         admin.require_auth()?;
         write_authorization(e, addr.clone(), authorize)?;
-        event::set_auth(e, admin, addr, authorize)?;
+        event::SetAuth {
+            admin,
+            id: addr,
+            authorize,
+        }
+        .publish(e)?;
         Ok(())
     }
 
@@ -295,16 +315,16 @@ impl TokenTrait for Token {
         check_admin(e, &admin)?;
         admin.require_auth()?;
         receive_balance(e, to.clone(), amount)?;
-        event::mint(e, admin, to, amount)?;
+        event::Mint { admin, to, amount }.publish(e)?;
         Ok(())
     }
 
     // Metering: covered by components
     fn set_admin(e: &Host, admin: Address, new_admin: Address) -> Result<(), HostError> {
         check_admin(e, &admin)?;
-        admin.require_auth()?;
+        admin.require_auth_with_threshold(ThresholdIndexes::High)?;
         write_administrator(e, new_admin.clone())?;
-        event::set_admin(e, admin, new_admin)?;
+        event::SetAdmin { admin, new_admin }.publish(e)?;
         Ok(())
     }
 
@@ -1,5 +1,6 @@
 pub(crate) mod util;
 
+mod auth;
 mod basic;
 mod bytes;
 mod crypto;
@@ -13,6 +14,7 @@ mod budget_metering;
 #[cfg(feature = "vm")]
 mod complex;
 mod event;
+mod fees;
 #[cfg(feature = "vm")]
 mod hostile;
 #[cfg(feature = "vm")]
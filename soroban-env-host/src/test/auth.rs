@@ -0,0 +1,425 @@
+use std::rc::Rc;
+
+use soroban_env_common::{
+    xdr::{
+        AccountId, AddressWithNonce, AuthorizedInvocation, ContractAuth, Hash, PublicKey,
+        ScAddress, ScHostAuthErrorCode, ScHostFnErrorCode, ScObject, ScVal, Uint256,
+    },
+    Env, EnvBase, Object, RawVal, Symbol, TryFromVal,
+};
+
+use crate::{
+    auth::{
+        export::{authorization_trees_to_dot, authorization_trees_to_json},
+        nonce_signature_expiration_ledger, unordered_nonce,
+    },
+    events::{contract_id_to_strkey, Event},
+    native_contract::{
+        account_contract::{AuthPolicyContext, InvokerContractAuthEntry},
+        base_types::{BytesN, Vec as HostVec},
+    },
+    ContractFunctionSet, Host, HostError, LedgerInfo,
+};
+
+const AUTHORIZER_ID: [u8; 32] = [1; 32];
+const FORWARDER_ID: [u8; 32] = [2; 32];
+const AUTH_CHECKER_ID: [u8; 32] = [3; 32];
+const POLICY_ACCOUNT_ID: [u8; 32] = [4; 32];
+const SOURCE_ACCOUNT: [u8; 32] = [7; 32];
+
+// Calls `FORWARDER.fwd()`, optionally authorizing `AUTH_CHECKER.req(self)`
+// on its own behalf first.
+struct AuthorizerContract;
+
+impl ContractFunctionSet for AuthorizerContract {
+    fn call(&self, func: &Symbol, host: &Host, args: &[RawVal]) -> Option<RawVal> {
+        if *func != Symbol::from_str("call") {
+            return None;
+        }
+        let do_authorize = bool::try_from_val(host, &args[0]).unwrap();
+        if do_authorize {
+            let self_address = host
+                .add_host_object(ScAddress::Contract(Hash(AUTHORIZER_ID)))
+                .unwrap();
+            let mut auth_args = HostVec::new(host).unwrap();
+            auth_args.push_raw(self_address.to_raw()).unwrap();
+            let entry = InvokerContractAuthEntry {
+                contract: BytesN::<32>::try_from_val(
+                    host,
+                    &host.bytes_new_from_slice(&AUTH_CHECKER_ID).unwrap(),
+                )
+                .unwrap(),
+                fn_name: Symbol::from_str("req"),
+                args: auth_args,
+                sub_invocs: HostVec::new(host).unwrap(),
+            };
+            let mut entries = HostVec::new(host).unwrap();
+            entries.push(&entry).unwrap();
+            host.authorize_as_curr_contract(entries.into()).unwrap();
+        }
+        Some(
+            host.call(
+                host.bytes_new_from_slice(&FORWARDER_ID).unwrap(),
+                Symbol::from_str("fwd"),
+                host.vec_new(RawVal::from_void()).unwrap(),
+            )
+            .unwrap(),
+        )
+    }
+}
+
+// Calls `AUTH_CHECKER.req(AUTHORIZER)`.
+struct ForwarderContract;
+
+impl ContractFunctionSet for ForwarderContract {
+    fn call(&self, func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        if *func != Symbol::from_str("fwd") {
+            return None;
+        }
+        let address = host
+            .add_host_object(ScAddress::Contract(Hash(AUTHORIZER_ID)))
+            .unwrap();
+        let args = host.vec_new(RawVal::from_void()).unwrap();
+        let args = host.vec_push_back(args, address.to_raw()).unwrap();
+        Some(
+            host.call(
+                host.bytes_new_from_slice(&AUTH_CHECKER_ID).unwrap(),
+                Symbol::from_str("req"),
+                args,
+            )
+            .unwrap(),
+        )
+    }
+}
+
+// Requires authorization of the address passed as its only argument.
+struct AuthCheckerContract;
+
+impl ContractFunctionSet for AuthCheckerContract {
+    fn call(&self, func: &Symbol, host: &Host, args: &[RawVal]) -> Option<RawVal> {
+        if *func != Symbol::from_str("req") {
+            return None;
+        }
+        let address = Object::try_from_val(host, &args[0]).unwrap();
+        Some(host.require_auth(address).unwrap())
+    }
+}
+
+fn register_contracts(host: &Host) -> Result<(), HostError> {
+    host.register_test_contract(
+        host.bytes_new_from_slice(&AUTHORIZER_ID)?,
+        Rc::new(AuthorizerContract),
+    )?;
+    host.register_test_contract(
+        host.bytes_new_from_slice(&FORWARDER_ID)?,
+        Rc::new(ForwarderContract),
+    )?;
+    host.register_test_contract(
+        host.bytes_new_from_slice(&AUTH_CHECKER_ID)?,
+        Rc::new(AuthCheckerContract),
+    )?;
+    Ok(())
+}
+
+fn call_authorizer(host: &Host, do_authorize: bool) -> Result<RawVal, HostError> {
+    let args = host.vec_new(RawVal::from_void())?;
+    let args = host.vec_push_back(args, RawVal::from_bool(do_authorize))?;
+    host.call(
+        host.bytes_new_from_slice(&AUTHORIZER_ID)?,
+        Symbol::from_str("call"),
+        args,
+    )
+}
+
+#[test]
+fn test_deep_sub_invocation_requires_contract_authorization() -> Result<(), HostError> {
+    let host = Host::test_host();
+    register_contracts(&host)?;
+
+    // `AUTH_CHECKER` is not called directly by `AUTHORIZER`, so the invoker
+    // authorization doesn't apply.
+    assert!(call_authorizer(&host, false).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_authorize_as_curr_contract() -> Result<(), HostError> {
+    let host = Host::test_host();
+    register_contracts(&host)?;
+
+    call_authorizer(&host, true)?;
+    // The authorization only lasts for the call that has made it.
+    assert!(call_authorizer(&host, false).is_err());
+    call_authorizer(&host, true)?;
+    Ok(())
+}
+
+fn debug_event_messages(host: &Host) -> Vec<String> {
+    host.get_events()
+        .unwrap()
+        .0
+        .iter()
+        .filter_map(|e| match &e.event {
+            Event::Debug(de) => de.msg.as_ref().map(|m| m.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_auth_failure_diagnostics() -> Result<(), HostError> {
+    let host = Host::test_host();
+    register_contracts(&host)?;
+    let source_account = AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([7; 32])));
+    host.set_source_account(source_account.clone());
+    let source_address = ScAddress::Account(source_account);
+    let other_address = ScAddress::Contract(Hash(AUTHORIZER_ID));
+
+    // Authorize `AUTH_CHECKER.req` with the arguments that don't match the
+    // actual call.
+    host.set_authorization_entries(vec![ContractAuth {
+        address_with_nonce: None,
+        root_invocation: AuthorizedInvocation {
+            contract_id: Hash(AUTH_CHECKER_ID),
+            function_name: "req".try_into().unwrap(),
+            args: vec![ScVal::Object(Some(ScObject::Address(other_address)))]
+                .try_into()
+                .unwrap(),
+            sub_invocations: Default::default(),
+        },
+        signature_args: Default::default(),
+    }])?;
+    let args = host.vec_new(RawVal::from_void())?;
+    let args = host.vec_push_back(args, host.add_host_object(source_address)?.to_raw())?;
+    let res = host.call(
+        host.bytes_new_from_slice(&AUTH_CHECKER_ID)?,
+        Symbol::from_str("req"),
+        args,
+    );
+    assert!(res.is_err());
+
+    let messages = debug_event_messages(&host);
+    assert!(messages
+        .iter()
+        .any(|m| m.starts_with("auth failed: address {} has not authorized call")));
+    assert!(messages
+        .iter()
+        .any(|m| m == "auth entry {}: {}, authorized args: {}"));
+    Ok(())
+}
+
+#[test]
+fn test_auth_failure_status_is_preserved() -> Result<(), HostError> {
+    let host = Host::test_host();
+    let address = host.add_host_object(ScAddress::Contract(Hash(AUTHORIZER_ID)))?;
+    let res = host.with_test_contract_frame(Hash(AUTH_CHECKER_ID), Symbol::from_str("req"), || {
+        host.require_auth(address)
+    });
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostAuthErrorCode::NotAuthorized
+    ));
+    assert!(debug_event_messages(&host)
+        .iter()
+        .any(|m| m == "auth failed: no authorization entries for the address"));
+    Ok(())
+}
+
+// Account contract that doesn't check any signatures, but only accepts
+// expiring signatures made for the transaction invoked by `SOURCE_ACCOUNT`.
+struct PolicyAccountContract;
+
+impl ContractFunctionSet for PolicyAccountContract {
+    fn call(&self, func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        if *func != Symbol::from_str("check_auth") {
+            return None;
+        }
+        let context =
+            AuthPolicyContext::try_from_val(host, &host.get_auth_policy_context().unwrap())
+                .unwrap();
+        let AuthPolicyContext::V1(context) = context;
+        assert_eq!(
+            nonce_signature_expiration_ledger(context.nonce).unwrap_or(0),
+            context.sig_exp
+        );
+        assert_eq!(context.network_id.to_array().unwrap(), [5; 32]);
+        assert_eq!(context.contexts.len().unwrap(), 1);
+        assert_eq!(
+            context.invoker.to_sc_address().unwrap(),
+            ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+                SOURCE_ACCOUNT
+            ))))
+        );
+        if context.sig_exp == 0 {
+            panic!("signature must expire");
+        }
+        Some(RawVal::from_void())
+    }
+}
+
+fn policy_account_auth_entry(nonce: u64) -> ContractAuth {
+    let address = ScAddress::Contract(Hash(POLICY_ACCOUNT_ID));
+    ContractAuth {
+        address_with_nonce: Some(AddressWithNonce {
+            address: address.clone(),
+            nonce,
+        }),
+        root_invocation: AuthorizedInvocation {
+            contract_id: Hash(AUTH_CHECKER_ID),
+            function_name: "req".try_into().unwrap(),
+            args: vec![ScVal::Object(Some(ScObject::Address(address)))]
+                .try_into()
+                .unwrap(),
+            sub_invocations: Default::default(),
+        },
+        signature_args: Default::default(),
+    }
+}
+
+#[test]
+fn test_auth_policy_context() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.set_ledger_info(LedgerInfo {
+        protocol_version: 20,
+        sequence_number: 100,
+        timestamp: 0,
+        network_id: [5; 32],
+        base_reserve: 0,
+    });
+    host.set_source_account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+        SOURCE_ACCOUNT,
+    ))));
+    register_contracts(&host)?;
+    host.register_test_contract(
+        host.bytes_new_from_slice(&POLICY_ACCOUNT_ID)?,
+        Rc::new(PolicyAccountContract),
+    )?;
+    let call_auth_checker = || -> Result<RawVal, HostError> {
+        let address = host.add_host_object(ScAddress::Contract(Hash(POLICY_ACCOUNT_ID)))?;
+        let args = host.vec_new(RawVal::from_void())?;
+        let args = host.vec_push_back(args, address.to_raw())?;
+        host.call(
+            host.bytes_new_from_slice(&AUTH_CHECKER_ID)?,
+            Symbol::from_str("req"),
+            args,
+        )
+    };
+
+    // The account policy rejects non-expiring signatures.
+    host.set_authorization_entries(vec![policy_account_auth_entry(0)])?;
+    assert!(call_auth_checker().is_err());
+
+    host.set_authorization_entries(vec![policy_account_auth_entry(unordered_nonce(200, 0))])?;
+    call_auth_checker()?;
+    Ok(())
+}
+
+#[test]
+fn test_auth_policy_context_outside_of_check_auth() -> Result<(), HostError> {
+    let host = Host::test_host();
+    let res = host.with_test_contract_frame(
+        Hash(POLICY_ACCOUNT_ID),
+        Symbol::from_str("check_auth"),
+        || Ok(host.get_auth_policy_context()?.to_raw()),
+    );
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostFnErrorCode::InputArgsInvalid
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_export_authorization_trees() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.switch_to_recording_auth();
+    register_contracts(&host)?;
+    let address = host.add_host_object(ScAddress::Contract(Hash(POLICY_ACCOUNT_ID)))?;
+    let args = host.vec_new(RawVal::from_void())?;
+    let args = host.vec_push_back(args, address.to_raw())?;
+    host.call(
+        host.bytes_new_from_slice(&AUTH_CHECKER_ID)?,
+        Symbol::from_str("req"),
+        args,
+    )?;
+
+    let trees = host.get_authorization_trees()?;
+    assert_eq!(trees.len(), 1);
+    assert_eq!(trees[0].root.function_name, "req");
+    assert!(trees[0].root.is_exhausted);
+
+    let json = authorization_trees_to_json(&trees);
+    assert!(json.starts_with(&format!(
+        "[{{\"address\":\"{}\",\"nonce\":0,\"root\":{{\"contract_id\":\"{}\",\"function_name\":\"req\",\"args\":[\"{}\"]",
+        contract_id_to_strkey(&Hash(POLICY_ACCOUNT_ID)),
+        contract_id_to_strkey(&Hash(AUTH_CHECKER_ID)),
+        contract_id_to_strkey(&Hash(POLICY_ACCOUNT_ID)),
+    )));
+    assert!(json.ends_with("\"is_exhausted\":true,\"sub_invocations\":[]}}]"));
+
+    let dot = authorization_trees_to_dot(&trees);
+    assert!(dot.starts_with("digraph authorizations {"));
+    assert!(dot.contains(&format!(
+        "label=\"{} (nonce 0)\";",
+        contract_id_to_strkey(&Hash(POLICY_ACCOUNT_ID))
+    )));
+    assert!(dot.contains("style=\"solid\""));
+    Ok(())
+}
+
+// Account contract that accepts any signatures.
+struct PermissiveAccountContract;
+
+impl ContractFunctionSet for PermissiveAccountContract {
+    fn call(&self, func: &Symbol, _host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        if *func != Symbol::from_str("check_auth") {
+            return None;
+        }
+        Some(RawVal::from_void())
+    }
+}
+
+#[test]
+fn test_nonce_mismatch_reports_stored_nonce() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    register_contracts(&host)?;
+    host.register_test_contract(
+        host.bytes_new_from_slice(&POLICY_ACCOUNT_ID)?,
+        Rc::new(PermissiveAccountContract),
+    )?;
+    let mut entry = policy_account_auth_entry(5);
+    // The test contract frame has no arguments.
+    entry.root_invocation.args = Default::default();
+    host.set_authorization_entries(vec![entry])?;
+
+    // Call `require_auth` directly, as the status is lost when the test
+    // contract panics.
+    let address = host.add_host_object(ScAddress::Contract(Hash(POLICY_ACCOUNT_ID)))?;
+    let res = host.with_test_contract_frame(Hash(AUTH_CHECKER_ID), Symbol::from_str("req"), || {
+        host.require_auth(address)
+    });
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostAuthErrorCode::NonceError
+    ));
+
+    // The address, the authorized nonce and the stored nonce are reported.
+    let events = host.get_events()?;
+    let nonce_mismatch = events
+        .0
+        .iter()
+        .find_map(|e| match &e.event {
+            Event::Debug(de)
+                if de.msg.as_deref()
+                    == Some(
+                        "nonce mismatch for address {}: authorized nonce {} doesn't match the stored nonce {}",
+                    ) =>
+            {
+                Some(de)
+            }
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(nonce_mismatch.args.len(), 3);
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::{
-    budget::CostType,
+    budget::{Budget, CostType},
     xdr::{ScMap, ScMapEntry, ScObject, ScVal, ScVmErrorCode},
     Env, Host, HostError, RawVal, Symbol,
 };
@@ -142,3 +142,171 @@ fn map_insert_key_vec_obj() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn vm_hostfn_profiling() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id_obj = host.register_test_contract_wasm(VEC)?;
+    let host = host
+        .test_budget(100_000, 100_000)
+        .enable_model(CostType::InvokeVmFunction)
+        .enable_model(CostType::InvokeHostFunction);
+    host.with_budget(|budget| budget.enable_profiling());
+
+    let sym = Symbol::from_str("vec_err");
+    let args = host.test_vec_obj::<u32>(&[1])?;
+    host.try_call(id_obj, sym.into(), args.into())?;
+
+    let profile = host
+        .with_budget(|budget| budget.disable_profiling())
+        .unwrap();
+    let hostfn_charges: Vec<_> = profile
+        .entries()
+        .iter()
+        .filter(|e| e.cost_type == CostType::InvokeHostFunction)
+        .map(|e| e.host_fn)
+        .collect();
+    assert_eq!(hostfn_charges, vec![Some("vec_new"), Some("vec_insert")]);
+
+    // The last entry carries the cumulative totals.
+    let last = profile.entries().last().unwrap();
+    host.with_budget(|budget| {
+        assert_eq!(last.cpu_insns, budget.get_cpu_insns_count());
+        assert_eq!(last.mem_bytes, budget.get_mem_bytes_count());
+    });
+    let totals = profile.totals_by_host_fn();
+    assert_eq!(totals.iter().map(|t| t.1).sum::<u64>(), last.cpu_insns);
+
+    let csv = profile.to_csv();
+    assert!(csv.starts_with("cost_type,input,host_fn,cpu_insns,mem_bytes\n"));
+    assert_eq!(csv.lines().count(), profile.entries().len() + 1);
+    assert!(profile
+        .to_json()
+        .contains("{\"cost_type\":\"InvokeHostFunction\",\"input\":1,\"host_fn\":\"vec_new\""));
+    Ok(())
+}
+
+#[test]
+fn profiling_enabled_mid_run() -> Result<(), HostError> {
+    let budget = Budget::default();
+    budget.reset_unlimited();
+    budget.charge(CostType::WasmInsnExec, 10)?;
+
+    // The guard of a host function that has started before profiling was
+    // enabled doesn't attribute anything.
+    let before = budget.enter_host_fn("before");
+    budget.enable_profiling();
+    budget.charge(CostType::WasmInsnExec, 10)?;
+    let outer = budget.enter_host_fn("outer");
+    // Restarting profiling while `outer` is running: dropping `outer` must
+    // not pop the host function pushed to the new profile.
+    budget.enable_profiling();
+    let cpu_at_start = budget.get_cpu_insns_count();
+    let inner = budget.enter_host_fn("inner");
+    drop(outer);
+    drop(before);
+    budget.charge(CostType::WasmInsnExec, 10)?;
+    drop(inner);
+    budget.charge(CostType::WasmInsnExec, 10)?;
+
+    let profile = budget.disable_profiling().unwrap();
+    let host_fns: Vec<_> = profile.entries().iter().map(|e| e.host_fn).collect();
+    assert_eq!(host_fns, vec![Some("inner"), None]);
+    // Only the consumption since the profile has been enabled is accounted
+    // for.
+    let total_cpu: u64 = profile.totals_by_host_fn().iter().map(|t| t.1).sum();
+    assert_eq!(total_cpu, budget.get_cpu_insns_count() - cpu_at_start);
+    Ok(())
+}
+
+#[test]
+fn allocation_check_reports_under_charged_types() -> Result<(), HostError> {
+    use crate::budget::AllocationCounter;
+    use std::{cell::Cell, rc::Rc};
+
+    struct MockCounter(Cell<u64>);
+    impl AllocationCounter for MockCounter {
+        fn allocated_bytes(&self) -> u64 {
+            self.0.get()
+        }
+    }
+
+    let host = Host::test_host()
+        .test_budget(100_000, 100_000)
+        .enable_model(CostType::ValXdrConv)
+        .enable_model(CostType::ValSer);
+    let counter = Rc::new(MockCounter(Cell::new(0)));
+    let (res, report) = host.with_allocation_check(counter.clone(), |host| {
+        host.charge_budget(CostType::ValXdrConv, 5)?;
+        counter.0.set(40);
+        host.charge_budget(CostType::ValSer, 2)?;
+        counter.0.set(41);
+        Ok::<(), HostError>(())
+    });
+    res?;
+
+    assert_eq!(report.0.len(), 2);
+    assert_eq!(report.0[0].cost_type, CostType::ValXdrConv);
+    assert_eq!(report.0[0].charged_bytes, 5);
+    assert_eq!(report.0[0].measured_bytes, 40);
+    assert_eq!(report.0[1].cost_type, CostType::ValSer);
+    assert_eq!(report.0[1].charged_bytes, 2);
+    assert_eq!(report.0[1].measured_bytes, 1);
+    let under: Vec<_> = report.under_charged().map(|d| d.cost_type).collect();
+    assert_eq!(under, vec![CostType::ValXdrConv]);
+    Ok(())
+}
+
+#[test]
+fn replay_charge_trace_against_new_models() -> Result<(), HostError> {
+    use crate::{
+        budget::{Budget, CostModel},
+        cost_runner::ChargeTrace,
+    };
+
+    let host = Host::test_host()
+        .test_budget(100_000, 100_000)
+        .enable_model(CostType::ValXdrConv)
+        .enable_model(CostType::MapEntry);
+    host.with_budget(|budget| budget.enable_profiling());
+    host.charge_budget(CostType::ValXdrConv, 5)?;
+    host.charge_budget(CostType::MapEntry, 3)?;
+    host.charge_budget(CostType::ValXdrConv, 2)?;
+    let profile = host
+        .with_budget(|budget| budget.disable_profiling())
+        .unwrap();
+
+    // Round-trip through CSV, as when moving a trace between host builds.
+    let trace = ChargeTrace::from_csv(&ChargeTrace::from_profile(&profile).to_csv())?;
+    assert_eq!(trace.0.len(), 3);
+
+    // Replaying against the recording budget reproduces its counts.
+    let base = host.with_budget(|budget| trace.replay(&budget));
+    host.with_budget(|budget| {
+        assert_eq!(base.total_cpu_insns(), budget.get_cpu_insns_count());
+        assert_eq!(base.total_mem_bytes(), budget.get_mem_bytes_count());
+    });
+
+    // Make MapEntry twice as expensive in cpu.
+    let budget = Budget::default();
+    for ct in CostType::variants() {
+        let (cpu, mem) = host.with_budget(|b| b.get_cost_models(*ct));
+        budget.set_cost_models(*ct, cpu, mem);
+    }
+    let (_, mem) = budget.get_cost_models(CostType::MapEntry);
+    let cpu = CostModel {
+        lin_param: 20,
+        ..Default::default()
+    };
+    budget.set_cost_models(CostType::MapEntry, cpu, mem);
+    let new = trace.replay(&budget);
+
+    let diff = base.diff(&new);
+    assert_eq!(diff.len(), 1);
+    assert_eq!(diff[0].cost_type, CostType::MapEntry);
+    assert_eq!(diff[0].base_cpu_insns, 30);
+    assert_eq!(diff[0].new_cpu_insns, 60);
+    assert_eq!(diff[0].cpu_insns_delta(), 30);
+    assert_eq!(diff[0].mem_bytes_delta(), 0);
+    Ok(())
+}
@@ -1,8 +1,14 @@
 use crate::{
+    native_contract::account_contract::{
+        base64_url_encode, check_webauthn_signature, parse_webauthn_authenticator_data,
+        parse_webauthn_client_data,
+    },
+    test::util::{webauthn_client_data, webauthn_signature},
     xdr::{ScObject, ScVal},
     Env, Host, HostError,
 };
 use hex::FromHex;
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
 
 /// crypto tests
 #[test]
@@ -68,3 +74,96 @@ fn ed25519_verify_test() -> Result<(), HostError> {
     };
     Ok(())
 }
+
+fn secp256r1_test_key() -> SigningKey {
+    SigningKey::from_bytes(&[7; 32]).unwrap()
+}
+
+#[test]
+fn ecdsa_secp256r1_verify_test() -> Result<(), HostError> {
+    let host = Host::default();
+    let key = secp256r1_test_key();
+    let message: &[u8] = b"hello";
+    let sig: Signature = key.sign(message);
+
+    let obj_pub = host.test_bin_obj(key.verifying_key().to_encoded_point(false).as_bytes())?;
+    let obj_msg = host.test_bin_obj(message)?;
+    let obj_sig = host.test_bin_obj(sig.as_ref())?;
+
+    host.verify_sig_ecdsa_secp256r1(obj_msg, obj_pub, obj_sig)
+        .expect("verification failed");
+
+    // Compressed public key
+    let obj_pub_compressed =
+        host.test_bin_obj(key.verifying_key().to_encoded_point(true).as_bytes())?;
+    host.verify_sig_ecdsa_secp256r1(obj_msg, obj_pub_compressed, obj_sig)
+        .expect("verification with compressed key failed");
+
+    // Now verify with wrong message
+    let obj_msg2 = host.test_bin_obj(b"hellp")?;
+    assert!(host
+        .verify_sig_ecdsa_secp256r1(obj_msg2, obj_pub, obj_sig)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn base64_url_encode_test() {
+    assert_eq!(base64_url_encode(b""), b"");
+    assert_eq!(base64_url_encode(b"f"), b"Zg");
+    assert_eq!(base64_url_encode(b"fo"), b"Zm8");
+    assert_eq!(base64_url_encode(b"foo"), b"Zm9v");
+    assert_eq!(base64_url_encode(&[0xfb, 0xff]), b"-_8");
+}
+
+#[test]
+fn webauthn_signature_test() -> Result<(), HostError> {
+    let host = Host::default();
+    let key = secp256r1_test_key();
+    let payload = [3u8; 32];
+    let client = webauthn_client_data(&payload);
+    let mut auth_data = [0u8; 37];
+    // User present and user verified flags.
+    auth_data[32] = 0x05;
+    auth_data[36] = 1;
+
+    let parsed = parse_webauthn_authenticator_data(&host, &auth_data)?;
+    assert_eq!(parsed.flags, 0x05);
+    assert_eq!(parsed.sign_count, 1);
+    assert_eq!(
+        parse_webauthn_client_data(&host, client.as_bytes())?.challenge,
+        base64_url_encode(&payload)
+    );
+    // The challenge string may also appear as a value before the member.
+    let shuffled = r#"{"origin":"challenge", "challenge" : "abc","type":"webauthn.get"}"#;
+    assert_eq!(
+        parse_webauthn_client_data(&host, shuffled.as_bytes())?.challenge,
+        b"abc"
+    );
+    assert!(parse_webauthn_client_data(&host, br#"{"type":"webauthn.get"}"#).is_err());
+
+    let sig = webauthn_signature(&host, &key, &auth_data, &client)?;
+    check_webauthn_signature(&host, &payload, &sig)?;
+
+    // Different payload
+    assert!(check_webauthn_signature(&host, &[4u8; 32], &sig).is_err());
+
+    // Wrong client data type
+    let sig = webauthn_signature(
+        &host,
+        &key,
+        &auth_data,
+        &client.replace("webauthn.get", "webauthn.create"),
+    )?;
+    assert!(check_webauthn_signature(&host, &payload, &sig).is_err());
+
+    // User not present
+    auth_data[32] = 0x04;
+    let sig = webauthn_signature(&host, &key, &auth_data, &client)?;
+    assert!(check_webauthn_signature(&host, &payload, &sig).is_err());
+
+    // Truncated authenticator data
+    let sig = webauthn_signature(&host, &key, &auth_data[..36], &client)?;
+    assert!(check_webauthn_signature(&host, &payload, &sig).is_err());
+    Ok(())
+}
@@ -1,13 +1,19 @@
 use crate::{
-    events::{DebugEvent, HostEvent},
+    events::{
+        contract_event_specs_to_json, token_event_specs, ContractEventLimitViolation,
+        ContractEventLimits, DebugArg, DebugEvent, Event, EventSink, EventSinkMode, Events,
+        HostEvent, LogEvent, LogLevel, PrettyContractEvent, PrettyScVal, TokenEvent, TraceEvent,
+    },
     xdr::{
         ContractEvent, ContractEventBody, ContractEventType, ContractEventV0, ExtensionPoint, Hash,
-        ScMap, ScMapEntry, ScObject::Map, ScVal,
+        Int128Parts, PublicKey, ScAddress, ScHostAuthErrorCode, ScHostFnErrorCode,
+        ScHostObjErrorCode, ScMap, ScMapEntry, ScObject, ScObject::Map, ScStatic, ScStatus,
+        ScStatusType, ScUnknownErrorCode, ScVal, ScVec, Uint256,
     },
-    ContractFunctionSet, Env, EnvBase, Host, HostError, RawVal, Symbol,
+    ContractFunctionSet, Env, EnvBase, Host, HostError, RawVal, Status, Symbol,
 };
 use expect_test::expect;
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 pub struct ContractWithSingleEvent;
 
@@ -55,8 +61,8 @@ fn contract_event() -> Result<(), HostError> {
     // Fish out the last contract event and check that it is
     // correct, and formats as expected.
     let events = host.get_events()?;
-    match events.0.last() {
-        Some(HostEvent::Contract(ce)) => {
+    match events.0.last().map(|e| &e.event) {
+        Some(Event::Contract(ce)) => {
             assert_eq!(*ce, event_ref)
         }
         _ => {
@@ -95,12 +101,974 @@ fn test_event_rollback() -> Result<(), HostError> {
         host.call(id, sym.into(), args.into())?.get_payload(),
         RawVal::from_void().get_payload()
     );
-    host.0.events.borrow_mut().rollback(1, &host)?;
+    host.0.events.borrow_mut().rollback(1, vec![], &host)?;
 
     let expected = expect![[
-        r#"Events([Contract(ContractEvent { ext: V0, contract_id: Some(Hash(0000000000000000000000000000000000000000000000000000000000000000)), type_: Contract, body: V0(ContractEventV0 { topics: ScVec(VecM([I32(0), I32(1)])), data: U32(0) }) }), Debug(DebugEvent { msg: Some("debug event 0"), args: [] }), Debug(DebugEvent { msg: Some("rolled-back contract event: type {}, id {}, topics {}, data {}"), args: [Val(I32(0)), Val(Object(Bytes(#4))), Val(Object(Vec(#2))), Val(U32(0))] }), Debug(DebugEvent { msg: Some("{} contract events rolled back. Rollback start pos = {}"), args: [Val(U32(1)), Val(U32(1))] })])"#
+        r#"Events([HostEvent { event: Contract(ContractEvent { ext: V0, contract_id: Some(Hash(0000000000000000000000000000000000000000000000000000000000000000)), type_: Contract, body: V0(ContractEventV0 { topics: ScVec(VecM([I32(0), I32(1)])), data: U32(0) }) }), in_successful_contract_call: true }, HostEvent { event: Debug(DebugEvent { msg: Some("debug event 0"), args: [] }), in_successful_contract_call: false }, HostEvent { event: Debug(DebugEvent { msg: Some("rolled-back contract event: type {}, id {}, topics {}, data {}"), args: [Val(I32(0)), Val(Object(Bytes(#4))), Val(Object(Vec(#2))), Val(U32(0))] }), in_successful_contract_call: false }, HostEvent { event: Debug(DebugEvent { msg: Some("{} contract events rolled back. Rollback start pos = {}"), args: [Val(U32(1)), Val(U32(1))] }), in_successful_contract_call: true }])"#
     ]];
     let actual = format!("{:?}", host.0.events.borrow().externalize(&host)?);
     expected.assert_eq(&actual);
     Ok(())
 }
+
+#[test]
+fn test_xdr_diagnostic_events() -> Result<(), HostError> {
+    let host = Host::default();
+    host.enable_xdr_diagnostic_events()?;
+    let dummy_id = [0; 32];
+    let id = host.bytes_new_from_slice(&dummy_id)?;
+    let test_contract = Rc::new(ContractWithMultipleEvents {});
+    let sym = Symbol::from_str("add");
+    let args = host.test_vec_obj::<i32>(&[1, 2])?;
+    host.register_test_contract(id, test_contract)?;
+    host.call(id, sym.into(), args.into())?;
+    // Debug events recorded outside of the contract have no contract id.
+    host.record_debug_event(DebugEvent::new().msg("value {}").arg(RawVal::from(7u32)))?;
+
+    let diagnostic_events: Vec<ContractEvent> = host
+        .get_events()?
+        .0
+        .into_iter()
+        .filter_map(|e| match e.event {
+            Event::Diagnostic(ce) => {
+                assert!(e.in_successful_contract_call);
+                Some(ce)
+            }
+            Event::Debug(_) => panic!("unexpected debug event"),
+            _ => None,
+        })
+        .collect();
+    let diagnostic_topic = ScVal::Symbol(host.map_err("diagnostic".try_into())?);
+    let expected = vec![
+        ContractEvent {
+            ext: ExtensionPoint::V0,
+            contract_id: Some(Hash(dummy_id)),
+            type_: ContractEventType::Diagnostic,
+            body: ContractEventBody::V0(ContractEventV0 {
+                topics: host.map_err(
+                    vec![
+                        diagnostic_topic.clone(),
+                        ScVal::Object(Some(ScObject::Bytes(
+                            host.map_err(b"debug event 0".to_vec().try_into())?,
+                        ))),
+                    ]
+                    .try_into(),
+                )?,
+                data: ScVal::Object(Some(ScObject::Vec(ScVec::default()))),
+            }),
+        },
+        ContractEvent {
+            ext: ExtensionPoint::V0,
+            contract_id: None,
+            type_: ContractEventType::Diagnostic,
+            body: ContractEventBody::V0(ContractEventV0 {
+                topics: host.map_err(
+                    vec![
+                        diagnostic_topic,
+                        ScVal::Object(Some(ScObject::Bytes(
+                            host.map_err(b"value U32(7)".to_vec().try_into())?,
+                        ))),
+                    ]
+                    .try_into(),
+                )?,
+                data: ScVal::Object(Some(ScObject::Vec(ScVec(
+                    host.map_err(vec![ScVal::U32(7)].try_into())?,
+                )))),
+            }),
+        },
+    ];
+    assert_eq!(diagnostic_events, expected);
+    Ok(())
+}
+
+pub struct FailingContract;
+
+impl ContractFunctionSet for FailingContract {
+    fn call(&self, _func: &Symbol, _host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        Some(Status::from_type_and_code(ScStatusType::ContractError, 7).into())
+    }
+}
+
+pub struct ContractWithNestedCalls;
+
+impl ContractFunctionSet for ContractWithNestedCalls {
+    fn call(&self, _func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        let event_id = host.bytes_new_from_slice(&[1; 32]).unwrap();
+        let failing_id = host.bytes_new_from_slice(&[2; 32]).unwrap();
+        let args = host.test_vec_obj::<u32>(&[5]).unwrap();
+        host.call(event_id, Symbol::from_str("event").into(), args.into())
+            .unwrap();
+        host.try_call(failing_id, Symbol::from_str("fail").into(), args.into())
+            .unwrap();
+        Some(().into())
+    }
+}
+
+#[test]
+fn test_call_trace_events() -> Result<(), HostError> {
+    let host = Host::default();
+    host.enable_call_trace_events()?;
+    for (id, contract) in [
+        (
+            [0; 32],
+            Rc::new(ContractWithNestedCalls {}) as Rc<dyn ContractFunctionSet>,
+        ),
+        ([1; 32], Rc::new(ContractWithSingleEvent {})),
+        ([2; 32], Rc::new(FailingContract {})),
+    ] {
+        host.register_test_contract(host.bytes_new_from_slice(&id)?, contract)?;
+    }
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    let args = host.test_vec_obj::<u32>(&[])?;
+    host.call(id, Symbol::from_str("nested").into(), args.into())?;
+
+    let events = host.get_events()?.0;
+    // The contract event has to be surrounded by the trace events of the
+    // contract that has emitted it.
+    let kinds: Vec<&str> = events
+        .iter()
+        .filter_map(|e| match &e.event {
+            Event::Contract(_) => Some("contract"),
+            Event::Trace(TraceEvent::Call { .. }) => Some("call"),
+            Event::Trace(TraceEvent::Return { .. }) => Some("return"),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec!["call", "call", "contract", "return", "call", "return", "return"]
+    );
+    // The budget consumed by the outer frame includes the nested frames.
+    let cpu_insns: Vec<u64> = events
+        .iter()
+        .filter_map(|e| match &e.event {
+            Event::Trace(TraceEvent::Return { cpu_insns, .. }) => Some(*cpu_insns),
+            _ => None,
+        })
+        .collect();
+    assert!(cpu_insns[2] >= cpu_insns[0] + cpu_insns[1]);
+
+    let traces: Vec<(
+        Hash,
+        Symbol,
+        Option<Vec<ScVal>>,
+        Option<Result<ScVal, ScStatus>>,
+    )> = events
+        .into_iter()
+        .filter_map(|e| match e.event {
+            Event::Trace(TraceEvent::Call {
+                contract_id,
+                function,
+                args,
+            }) => Some((contract_id?, function?, Some(args), None)),
+            Event::Trace(TraceEvent::Return {
+                contract_id,
+                function,
+                result,
+                ..
+            }) => Some((contract_id?, function?, None, Some(result))),
+            _ => None,
+        })
+        .collect();
+    let void = ScVal::Static(ScStatic::Void);
+    assert_eq!(
+        traces,
+        vec![
+            (
+                Hash([0; 32]),
+                Symbol::from_str("nested"),
+                Some(vec![]),
+                None
+            ),
+            (
+                Hash([1; 32]),
+                Symbol::from_str("event"),
+                Some(vec![ScVal::U32(5)]),
+                None
+            ),
+            (
+                Hash([1; 32]),
+                Symbol::from_str("event"),
+                None,
+                Some(Ok(void.clone()))
+            ),
+            (
+                Hash([2; 32]),
+                Symbol::from_str("fail"),
+                Some(vec![ScVal::U32(5)]),
+                None
+            ),
+            (
+                Hash([2; 32]),
+                Symbol::from_str("fail"),
+                None,
+                Some(Err(ScStatus::ContractError(7)))
+            ),
+            (
+                Hash([0; 32]),
+                Symbol::from_str("nested"),
+                None,
+                Some(Ok(void))
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_call_trace_keeps_results_that_cannot_be_traced() -> Result<(), HostError> {
+    let host = Host::default();
+    host.enable_call_trace_events()?;
+    // Auth errors have no XDR representation, so tracing records a fallback
+    // status instead of replacing the error.
+    let address = host.add_host_object(ScAddress::Contract(Hash([1; 32])))?;
+    let res = host.with_test_contract_frame(Hash([0; 32]), Symbol::from_str("req"), || {
+        host.require_auth(address)
+    });
+    assert!(HostError::result_matches_err_status(
+        res,
+        ScHostAuthErrorCode::NotAuthorized
+    ));
+    let results: Vec<Result<ScVal, ScStatus>> = host
+        .get_events()?
+        .0
+        .into_iter()
+        .filter_map(|e| match e.event {
+            Event::Trace(TraceEvent::Return { result, .. }) => Some(result),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        results,
+        vec![Err(ScStatus::UnknownError(ScUnknownErrorCode::General))]
+    );
+    Ok(())
+}
+
+pub struct ContractWithFailureAfterEvent;
+
+impl ContractFunctionSet for ContractWithFailureAfterEvent {
+    fn call(&self, func: &Symbol, host: &Host, args: &[RawVal]) -> Option<RawVal> {
+        ContractWithSingleEvent {}.call(func, host, args);
+        Some(Status::from_type_and_code(ScStatusType::ContractError, 1).into())
+    }
+}
+
+#[test]
+fn test_rolled_back_events_query() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    host.register_test_contract(id, Rc::new(ContractWithFailureAfterEvent {}))?;
+    let args = host.test_vec_obj::<u32>(&[])?;
+    host.try_call(id, Symbol::from_str("fail").into(), args.into())?;
+
+    let events = host.get_events()?;
+    assert_eq!(events.query().iter().count(), 0);
+    let rolled_back: Vec<&ContractEvent> = events.query().rolled_back_only().iter().collect();
+    assert_eq!(rolled_back.len(), 1);
+    assert_eq!(rolled_back[0].contract_id, Some(Hash([0; 32])));
+    let ContractEventBody::V0(body) = &rolled_back[0].body;
+    assert_eq!(
+        body.topics,
+        host.map_err(vec![ScVal::U32(0), ScVal::U32(1)].try_into())?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_failed_call_events_are_marked() -> Result<(), HostError> {
+    let host = Host::default();
+    host.enable_xdr_diagnostic_events()?;
+    host.enable_call_trace_events()?;
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    host.register_test_contract(id, Rc::new(ContractWithFailureAfterEvent {}))?;
+    let args = host.test_vec_obj::<u32>(&[])?;
+    host.try_call(id, Symbol::from_str("fail").into(), args.into())?;
+
+    let events: Vec<(&str, bool)> = host
+        .get_events()?
+        .0
+        .into_iter()
+        .map(|e| {
+            let kind = match e.event {
+                Event::Contract(_) => "contract",
+                Event::Diagnostic(_) => "diagnostic",
+                Event::Trace(TraceEvent::Call { .. }) => "call",
+                Event::Trace(TraceEvent::Return { .. }) => "return",
+                Event::Debug(_) | Event::Log(_) => "other",
+            };
+            (kind, e.in_successful_contract_call)
+        })
+        .collect();
+    // The contract event and the error of the failed call are kept, but
+    // marked, while the call trace events, the rollback summary and the
+    // `try_call` error are recorded outside of the failed call.
+    assert_eq!(
+        events,
+        vec![
+            ("call", true),
+            ("contract", false),
+            ("diagnostic", false),
+            ("diagnostic", true),
+            ("return", true),
+            ("diagnostic", true),
+        ]
+    );
+    Ok(())
+}
+
+#[derive(Default)]
+struct RecordingSink {
+    events: RefCell<Vec<HostEvent>>,
+    // Positions of the first rolled back event, one per rollback.
+    rollbacks: RefCell<Vec<usize>>,
+}
+
+impl EventSink for RecordingSink {
+    fn receive(&self, event: HostEvent) {
+        self.events.borrow_mut().push(event)
+    }
+
+    fn rollback(&self, count: usize) {
+        let start = self.events.borrow().len() - count;
+        self.rollbacks.borrow_mut().push(start)
+    }
+}
+
+fn contract_event_positions(events: &[HostEvent]) -> Vec<usize> {
+    events
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| matches!(e.event, Event::Contract(_)).then_some(i))
+        .collect()
+}
+
+#[test]
+fn test_event_sink_on_commit() -> Result<(), HostError> {
+    let host = Host::default();
+    let sink = Rc::new(RecordingSink::default());
+    host.set_event_sink(sink.clone(), EventSinkMode::OnCommit);
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    host.register_test_contract(id, Rc::new(ContractWithSingleEvent {}))?;
+    let args = host.test_vec_obj::<u32>(&[])?;
+
+    host.call(id, Symbol::from_str("event").into(), args.into())?;
+    assert_eq!(contract_event_positions(&sink.events.borrow()).len(), 1);
+    // The events handed to the sink are still kept by the host.
+    assert_eq!(contract_event_positions(&host.get_events()?.0).len(), 1);
+
+    host.call(id, Symbol::from_str("event").into(), args.into())?;
+    assert_eq!(contract_event_positions(&sink.events.borrow()).len(), 2);
+    assert!(sink.rollbacks.borrow().is_empty());
+    Ok(())
+}
+
+// Emits an event, calls `ContractWithSingleEvent` (registered with the id
+// `[1; 32]`) and then fails.
+pub struct ContractWithFailureAfterCall;
+
+impl ContractFunctionSet for ContractWithFailureAfterCall {
+    fn call(&self, func: &Symbol, host: &Host, args: &[RawVal]) -> Option<RawVal> {
+        ContractWithSingleEvent {}.call(func, host, args);
+        let id = host.bytes_new_from_slice(&[1; 32]).unwrap();
+        let args = host.test_vec_obj::<u32>(&[]).unwrap();
+        host.call(id, Symbol::from_str("event").into(), args.into())
+            .unwrap();
+        Some(Status::from_type_and_code(ScStatusType::ContractError, 1).into())
+    }
+}
+
+#[test]
+fn test_event_sink_immediate() -> Result<(), HostError> {
+    let host = Host::default();
+    let sink = Rc::new(RecordingSink::default());
+    host.set_event_sink(sink.clone(), EventSinkMode::Immediate);
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    host.register_test_contract(id, Rc::new(ContractWithFailureAfterCall {}))?;
+    host.register_test_contract(
+        host.bytes_new_from_slice(&[1; 32])?,
+        Rc::new(ContractWithSingleEvent {}),
+    )?;
+    let args = host.test_vec_obj::<u32>(&[])?;
+
+    host.try_call(id, Symbol::from_str("fail").into(), args.into())?;
+    // The contract events have been handed over at the boundaries of the
+    // inner call before the failure, and then reported as rolled back.
+    let positions = contract_event_positions(&sink.events.borrow());
+    assert_eq!(positions.len(), 2);
+    let rollbacks = sink.rollbacks.borrow().clone();
+    assert_eq!(rollbacks.len(), 1);
+    assert!(rollbacks[0] <= positions[0]);
+    // The host keeps the events handed over.
+    assert_eq!(contract_event_positions(&host.get_events()?.0), positions);
+    // The rollback summary is handed over after the rollback.
+    assert!(matches!(
+        sink.events.borrow().last().map(|e| &e.event),
+        Some(Event::Debug(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_dump_events_to_debug_log() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    host.register_test_contract(id, Rc::new(ContractWithSingleEvent {}))?;
+    let args = host.test_vec_obj::<u32>(&[])?;
+    host.call(id, Symbol::from_str("event").into(), args.into())?;
+
+    // Dumping the events doesn't affect the metering.
+    let budget = host.budget_cloned();
+    let cpu_insns = budget.get_cpu_insns_count();
+    host.dump_events_to_debug_log();
+    assert_eq!(budget.get_cpu_insns_count(), cpu_insns);
+    Ok(())
+}
+
+// Sink that hands the host over to `next` as soon as it receives an event.
+struct SwitchingSink {
+    host: Host,
+    next: Rc<RecordingSink>,
+    received: RefCell<usize>,
+}
+
+impl EventSink for SwitchingSink {
+    fn receive(&self, _event: HostEvent) {
+        *self.received.borrow_mut() += 1;
+        self.host
+            .set_event_sink(self.next.clone(), EventSinkMode::Immediate);
+    }
+}
+
+#[test]
+fn test_event_sink_can_replace_itself() -> Result<(), HostError> {
+    let host = Host::default();
+    let next = Rc::new(RecordingSink::default());
+    let sink = Rc::new(SwitchingSink {
+        host: host.clone(),
+        next: next.clone(),
+        received: Default::default(),
+    });
+    host.set_event_sink(sink.clone(), EventSinkMode::OnCommit);
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    host.register_test_contract(id, Rc::new(ContractWithSingleEvent {}))?;
+    let args = host.test_vec_obj::<u32>(&[])?;
+
+    host.call(id, Symbol::from_str("event").into(), args.into())?;
+    assert!(*sink.received.borrow() > 0);
+    host.call(id, Symbol::from_str("event").into(), args.into())?;
+    assert_eq!(contract_event_positions(&next.events.borrow()).len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_event_rendering() -> Result<(), HostError> {
+    let host = Host::default();
+    let amount = (-5i128) as u128;
+    let val = ScVal::Object(Some(Map(host.map_err(ScMap::try_from(vec![
+        ScMapEntry {
+            key: ScVal::Symbol(host.map_err("to".try_into())?),
+            val: ScVal::Object(Some(ScObject::Address(ScAddress::Account(
+                crate::xdr::AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([0; 32]))),
+            )))),
+        },
+        ScMapEntry {
+            key: ScVal::Symbol(host.map_err("amount".try_into())?),
+            val: ScVal::Object(Some(ScObject::I128(Int128Parts {
+                lo: amount as u64,
+                hi: (amount >> 64) as u64,
+            }))),
+        },
+    ]))?)));
+    expect!["{Symbol(to): GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF, Symbol(amount): I128(-5)}"]
+        .assert_eq(&PrettyScVal(&val).to_string());
+
+    let event = ContractEvent {
+        ext: ExtensionPoint::V0,
+        contract_id: Some(Hash([1; 32])),
+        type_: ContractEventType::Contract,
+        body: ContractEventBody::V0(ContractEventV0 {
+            topics: host.map_err(
+                vec![
+                    ScVal::Symbol(host.map_err("transfer".try_into())?),
+                    ScVal::Object(Some(ScObject::Address(ScAddress::Contract(Hash([0; 32]))))),
+                ]
+                .try_into(),
+            )?,
+            data: ScVal::Object(Some(ScObject::Bytes(
+                host.map_err(vec![1u8, 2].try_into())?,
+            ))),
+        }),
+    };
+    expect!["Contract event from CAAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQC526: topics [Symbol(transfer), CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4], data Bytes(0102)"]
+        .assert_eq(&PrettyContractEvent(&event).to_string());
+
+    // The raw values are rendered via the host.
+    let vec = host.test_vec_obj::<u32>(&[1, 2])?;
+    let nested = host.vec_push_back(host.vec_new(().into())?, vec.to_raw())?;
+    expect!["[[U32(1), U32(2)], Symbol(abc)]"].assert_eq(
+        &host.render_val(
+            host.vec_push_back(nested, Symbol::from_str("abc").into())?
+                .into(),
+        ),
+    );
+    let mut debug = HostEvent {
+        event: Event::Debug(DebugEvent::new().msg("got {}").arg(RawVal::from(vec))),
+        in_successful_contract_call: true,
+    };
+    expect!["debug event: got [U32(1), U32(2)]"].assert_eq(&host.render_event(&debug));
+    debug.in_successful_contract_call = false;
+    expect!["[failed call] debug event: got [U32(1), U32(2)]"]
+        .assert_eq(&host.render_event(&debug));
+    Ok(())
+}
+
+pub struct ContractWithLogs;
+
+impl ContractFunctionSet for ContractWithLogs {
+    fn call(&self, _func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        let values = host.test_vec_obj::<u32>(&[1]).unwrap();
+        for level in [LogLevel::Debug, LogLevel::Warn] {
+            host.log((level as u32).into(), Symbol::from_str("tx"), values)
+                .unwrap();
+        }
+        Some(().into())
+    }
+}
+
+#[test]
+fn test_contract_logs() -> Result<(), HostError> {
+    let host = Host::default();
+    host.set_min_log_level(LogLevel::Info)?;
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    host.register_test_contract(id, Rc::new(ContractWithLogs {}))?;
+    let args = host.test_vec_obj::<u32>(&[])?;
+    host.call(id, Symbol::from_str("log").into(), args.into())?;
+
+    // Only the records at or above the minimum level are recorded.
+    let logs: Vec<LogEvent> = host
+        .get_events()?
+        .0
+        .into_iter()
+        .filter_map(|e| match e.event {
+            Event::Log(le) => Some(le),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        logs,
+        vec![LogEvent {
+            level: LogLevel::Warn,
+            contract_id: Some(Hash([0; 32])),
+            target: Symbol::from_str("tx"),
+            values: vec![ScVal::U32(1)],
+        }]
+    );
+
+    // With the XDR diagnostics, the records are converted to the
+    // `Diagnostic` contract events.
+    host.enable_xdr_diagnostic_events()?;
+    host.call(id, Symbol::from_str("log").into(), args.into())?;
+    let events = host.get_events()?;
+    let log_topic = vec![
+        ScVal::Symbol(host.map_err("log".try_into())?),
+        ScVal::Symbol(host.map_err("warn".try_into())?),
+        ScVal::Symbol(host.map_err("tx".try_into())?),
+    ];
+    let diagnostics: Vec<&ContractEvent> = events
+        .query()
+        .with_type(ContractEventType::Diagnostic)
+        .with_topic_prefix(log_topic)
+        .iter()
+        .collect();
+    assert_eq!(diagnostics.len(), 1);
+
+    let values = host.test_vec_obj::<u32>(&[])?;
+    assert!(HostError::result_matches_err_status(
+        host.log(5u32.into(), Symbol::from_str("tx"), values),
+        ScHostFnErrorCode::InputArgsInvalid
+    ));
+    // The values are validated even when the record is filtered out.
+    let map = host.map_new()?;
+    assert!(HostError::result_matches_err_status(
+        host.log((LogLevel::Debug as u32).into(), Symbol::from_str("tx"), map),
+        ScHostObjErrorCode::UnexpectedType
+    ));
+    Ok(())
+}
+
+fn token_contract_event(
+    host: &Host,
+    contract_id: [u8; 32],
+    name: &str,
+    addresses: &[ScAddress],
+    amount: i128,
+) -> Result<ContractEvent, HostError> {
+    let mut topics = vec![ScVal::Symbol(host.map_err(name.try_into())?)];
+    for address in addresses {
+        topics.push(ScVal::Object(Some(ScObject::Address(address.clone()))));
+    }
+    Ok(ContractEvent {
+        ext: ExtensionPoint::V0,
+        contract_id: Some(Hash(contract_id)),
+        type_: ContractEventType::Contract,
+        body: ContractEventBody::V0(ContractEventV0 {
+            topics: host.map_err(topics.try_into())?,
+            data: ScVal::Object(Some(ScObject::I128(Int128Parts {
+                lo: amount as u64,
+                hi: (amount >> 64) as u64,
+            }))),
+        }),
+    })
+}
+
+#[test]
+fn test_token_event_specs() -> Result<(), HostError> {
+    let host = Host::default();
+    let a = ScAddress::Contract(Hash([10; 32]));
+    let b = ScAddress::Contract(Hash([11; 32]));
+    let specs = token_event_specs();
+    let names: Vec<&str> = specs.iter().map(|s| s.name).collect();
+    assert_eq!(
+        names,
+        vec![
+            "incr_allow",
+            "decr_allow",
+            "transfer",
+            "mint",
+            "clawback",
+            "set_auth",
+            "set_admin",
+            "burn"
+        ]
+    );
+
+    let transfer = token_contract_event(&host, [1; 32], "transfer", &[a.clone(), b.clone()], 7)?;
+    let decoded: Vec<_> = specs.iter().filter_map(|s| s.decode(&transfer)).collect();
+    assert_eq!(decoded.len(), 1);
+    let params: Vec<&str> = decoded[0].iter().map(|(name, _)| *name).collect();
+    assert_eq!(params, vec!["from", "to", "amount"]);
+    assert_eq!(
+        decoded[0][1].1,
+        ScVal::Object(Some(ScObject::Address(b.clone())))
+    );
+
+    // The events that don't have the described shape are not decoded.
+    let burn_with_extra_topic = token_contract_event(&host, [1; 32], "burn", &[a, b], 7)?;
+    assert!(specs
+        .iter()
+        .all(|s| s.decode(&burn_with_extra_topic).is_none()));
+    Ok(())
+}
+
+#[test]
+fn test_token_event_specs_json() {
+    let specs = token_event_specs();
+    expect![[r#"{"name":"transfer","topics":[{"name":"from","type":"Address"},{"name":"to","type":"Address"}],"data":{"name":"amount","type":"I128"}}"#]]
+        .assert_eq(&specs[2].to_json());
+    let json = contract_event_specs_to_json(specs);
+    assert!(json.starts_with(&format!("[{},", specs[0].to_json())));
+    assert!(json.ends_with(&format!(",{}]", specs[specs.len() - 1].to_json())));
+}
+
+#[test]
+fn test_events_query() -> Result<(), HostError> {
+    let host = Host::default();
+    let a = ScAddress::Contract(Hash([10; 32]));
+    let b = ScAddress::Contract(Hash([11; 32]));
+    let transfer = token_contract_event(&host, [1; 32], "transfer", &[a.clone(), b.clone()], 10)?;
+    let mint = token_contract_event(&host, [1; 32], "mint", &[a.clone(), b.clone()], -5)?;
+    let other_transfer =
+        token_contract_event(&host, [2; 32], "transfer", &[b.clone(), a.clone()], 7)?;
+    let system = ContractEvent {
+        ext: ExtensionPoint::V0,
+        contract_id: None,
+        type_: ContractEventType::System,
+        body: ContractEventBody::V0(ContractEventV0 {
+            topics: host.map_err(vec![ScVal::U32(1)].try_into())?,
+            data: ScVal::U32(2),
+        }),
+    };
+    let events = Events(
+        vec![
+            (Event::Contract(transfer.clone()), true),
+            (Event::Debug(DebugEvent::new().msg("debug")), true),
+            (Event::Contract(mint.clone()), false),
+            (Event::Contract(other_transfer.clone()), true),
+            (Event::Contract(system.clone()), true),
+        ]
+        .into_iter()
+        .map(|(event, in_successful_contract_call)| HostEvent {
+            event,
+            in_successful_contract_call,
+        })
+        .collect(),
+    );
+
+    assert_eq!(
+        events.query().iter().collect::<Vec<_>>(),
+        vec![&transfer, &other_transfer, &system]
+    );
+    assert_eq!(
+        events
+            .query()
+            .with_type(ContractEventType::System)
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![&system]
+    );
+    assert_eq!(
+        events
+            .query()
+            .with_topic_prefix(vec![
+                ScVal::Symbol(host.map_err("transfer".try_into())?),
+                ScVal::Object(Some(ScObject::Address(b.clone()))),
+            ])
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![&other_transfer]
+    );
+    assert_eq!(
+        events
+            .query()
+            .with_contract_id(Hash([1; 32]))
+            .include_rolled_back()
+            .iter_with_status()
+            .collect::<Vec<_>>(),
+        vec![(&transfer, false), (&mint, true)]
+    );
+    assert_eq!(
+        events
+            .query()
+            .with_contract_id(Hash([1; 32]))
+            .include_rolled_back()
+            .token_events()
+            .collect::<Vec<_>>(),
+        vec![
+            TokenEvent::Transfer {
+                from: a.clone(),
+                to: b.clone(),
+                amount: 10
+            },
+            TokenEvent::Mint {
+                admin: a,
+                to: b,
+                amount: -5
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_contract_event_validation() -> Result<(), HostError> {
+    use ContractEventLimitViolation::*;
+
+    // Every violation is reported with its own status.
+    let violations = [
+        TooManyTopics,
+        TopicTypeNotAllowed,
+        TopicBytesTooLong,
+        ContainerTooLong,
+        ContainerTooDeep,
+        TopicsTooLarge,
+        DataTooLarge,
+        TooManyInvocationEvents,
+        InvocationEventsTooLarge,
+    ];
+    for (i, a) in violations.iter().enumerate() {
+        for b in &violations[i + 1..] {
+            assert_ne!(a.status(), b.status());
+        }
+    }
+
+    let host = Host::default();
+    let check = |res: Result<usize, HostError>, violation: ContractEventLimitViolation| {
+        let err = res.unwrap_err();
+        assert_eq!(err.status, violation.status());
+        match err
+            .events
+            .and_then(|events| events.0.last().map(|e| e.event.clone()))
+        {
+            Some(Event::Debug(de)) => {
+                assert!(
+                    matches!(de.args.first(), Some(DebugArg::Str(name)) if *name == violation.name())
+                )
+            }
+            _ => panic!("the violation has not been reported"),
+        }
+    };
+    let small_vec = host.test_vec_obj::<u32>(&[1, 2, 3])?;
+    let nested_vec = host.vec_new(RawVal::from_void())?;
+    let nested_vec = host.vec_push_back(nested_vec, small_vec.into())?;
+
+    host.validate_contract_event_topics(host.test_vec_obj::<u32>(&[0, 1])?)?;
+    host.validate_contract_event_data(nested_vec.into())?;
+    check(
+        host.validate_contract_event_topics(host.test_vec_obj::<u32>(&[0, 1, 2, 3, 4])?),
+        TooManyTopics,
+    );
+    let long_bytes = host.bytes_new_from_slice(&[0; 33])?;
+    let topics = host.vec_new(RawVal::from_void())?;
+    let topics = host.vec_push_back(topics, long_bytes.into())?;
+    check(
+        host.validate_contract_event_topics(topics),
+        TopicBytesTooLong,
+    );
+
+    host.set_contract_event_limits(ContractEventLimits {
+        max_container_length: 2,
+        ..Default::default()
+    });
+    check(
+        host.validate_contract_event_data(small_vec.into()),
+        ContainerTooLong,
+    );
+
+    host.set_contract_event_limits(ContractEventLimits {
+        max_container_depth: 1,
+        ..Default::default()
+    });
+    host.validate_contract_event_data(small_vec.into())?;
+    check(
+        host.validate_contract_event_data(nested_vec.into()),
+        ContainerTooDeep,
+    );
+
+    host.set_contract_event_limits(ContractEventLimits {
+        max_topics_size: 8,
+        max_data_size: 8,
+        ..Default::default()
+    });
+    check(
+        host.validate_contract_event_topics(host.test_vec_obj::<u32>(&[0, 1])?),
+        TopicsTooLarge,
+    );
+    host.validate_contract_event_data(RawVal::from(1u32))?;
+    check(
+        host.validate_contract_event_data(long_bytes.into()),
+        DataTooLarge,
+    );
+    Ok(())
+}
+
+pub struct ContractWithTwoEvents;
+
+impl ContractFunctionSet for ContractWithTwoEvents {
+    fn call(&self, _func: &Symbol, host: &Host, _args: &[RawVal]) -> Option<RawVal> {
+        let topics = host.test_vec_obj(&[0, 1]).unwrap();
+        for _ in 0..2 {
+            if let Err(e) =
+                host.record_contract_event(ContractEventType::Contract, topics, 0u32.into())
+            {
+                return Some(e.status.into());
+            }
+        }
+        Some(().into())
+    }
+}
+
+#[test]
+fn test_invocation_events_limits() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    host.register_test_contract(id, Rc::new(ContractWithTwoEvents {}))?;
+    let call = || {
+        let args = host.test_vec_obj::<u32>(&[])?;
+        host.call(id, Symbol::from_str("emit").into(), args.into())
+    };
+
+    // The limits apply per invocation, so the usage is reset after every
+    // call.
+    host.set_contract_event_limits(ContractEventLimits {
+        max_invocation_events_count: 2,
+        ..Default::default()
+    });
+    call()?;
+    call()?;
+
+    host.set_contract_event_limits(ContractEventLimits {
+        max_invocation_events_count: 1,
+        ..Default::default()
+    });
+    assert!(HostError::result_matches_err_status(
+        call(),
+        ContractEventLimitViolation::TooManyInvocationEvents.status()
+    ));
+
+    // Every event in `ContractWithTwoEvents` has 2 `I32` topics and `U32`
+    // data, which is 40 bytes in XDR.
+    host.set_contract_event_limits(ContractEventLimits {
+        max_invocation_events_size: 40 + 39,
+        ..Default::default()
+    });
+    assert!(HostError::result_matches_err_status(
+        call(),
+        ContractEventLimitViolation::InvocationEventsTooLarge.status()
+    ));
+    host.set_contract_event_limits(ContractEventLimits {
+        max_invocation_events_size: 2 * 40,
+        ..Default::default()
+    });
+    call()?;
+    Ok(())
+}
+
+#[test]
+fn debug_events_charge_shadow_budget() -> Result<(), HostError> {
+    use crate::budget::CostType;
+
+    let host = Host::test_host()
+        .test_budget(100_000, 100_000)
+        .enable_model(CostType::HostEventDebug);
+    host.with_budget(|budget| budget.reset_shadow_limits(100_000, 100_000));
+    host.record_debug_event(DebugEvent::new().msg("shadow 0"))?;
+    host.with_budget(|budget| {
+        assert_eq!(budget.get_cpu_insns_count(), 0);
+        assert_eq!(budget.get_mem_bytes_count(), 0);
+        assert_eq!(budget.get_input(CostType::HostEventDebug), 0);
+        assert_eq!(budget.get_shadow_cpu_insns_count(), 10);
+        assert_eq!(budget.get_shadow_mem_bytes_count(), 1);
+    });
+    assert_eq!(host.get_events()?.0.len(), 1);
+
+    // Once the shadow budget is exhausted, debug events are dropped rather
+    // than failing the caller, and the main budget remains untouched.
+    host.with_budget(|budget| budget.reset_shadow_limits(0, 0));
+    host.record_debug_event(DebugEvent::new().msg("shadow 1"))?;
+    assert_eq!(host.get_events()?.0.len(), 1);
+    host.with_budget(|budget| {
+        assert_eq!(budget.get_cpu_insns_count(), 0);
+        assert_eq!(budget.get_mem_bytes_count(), 0);
+    });
+    Ok(())
+}
+
+#[test]
+fn diagnostics_are_not_charged_to_main_budget() -> Result<(), HostError> {
+    use crate::budget::CostType;
+
+    // Records a contract event surrounded by `debug_events` debug events
+    // within a frame that is rolled back, and returns the main budget usage.
+    let run = |debug_events: u32| -> Result<(u64, u64), HostError> {
+        let host = Host::test_host()
+            .test_budget(100_000, 100_000)
+            .enable_model(CostType::VecNew);
+        host.with_budget(|budget| budget.reset_shadow_limits(100_000, 100_000));
+        let topics = host.test_vec_obj::<u32>(&[1])?;
+        let res = host.with_test_contract_frame(Hash([0; 32]), Symbol::from_str("test"), || {
+            for _ in 0..debug_events {
+                host.record_debug_event(DebugEvent::new().msg("before"))?;
+            }
+            host.contract_event(topics, RawVal::from(1u32))?;
+            for _ in 0..debug_events {
+                host.record_debug_event(DebugEvent::new().msg("after"))?;
+            }
+            Err(host.err_general("roll back"))
+        });
+        assert!(res.is_err());
+        Ok(host.with_budget(|budget| (budget.get_cpu_insns_count(), budget.get_mem_bytes_count())))
+    };
+    assert_eq!(run(0)?, run(10)?);
+    Ok(())
+}
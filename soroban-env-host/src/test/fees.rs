@@ -0,0 +1,104 @@
+use crate::{
+    events::Event,
+    fees::{compute_fee, FeeBreakdown, FeeSchedule, InvocationResources},
+    test::event::ContractWithSingleEvent,
+    xdr::WriteXdr,
+    Env, EnvBase, Host, HostError, Symbol,
+};
+use std::rc::Rc;
+
+fn test_schedule() -> FeeSchedule {
+    FeeSchedule {
+        fee_per_instruction_increment: 100,
+        fee_per_mem_1kb: 10,
+        fee_per_read_entry: 1000,
+        fee_per_write_entry: 3000,
+        fee_per_read_1kb: 50,
+        fee_per_write_1kb: 200,
+        fee_per_contract_event_1kb: 300,
+    }
+}
+
+#[test]
+fn fee_breakdown_rounds_up() {
+    let resources = InvocationResources {
+        cpu_insns: 25_000,
+        mem_bytes: 2048,
+        read_entries: 3,
+        write_entries: 1,
+        read_bytes: 1025,
+        write_bytes: 100,
+        contract_events_bytes: 1,
+    };
+    let fee = compute_fee(&resources, &test_schedule());
+    assert_eq!(
+        fee,
+        FeeBreakdown {
+            // ceil(25_000 * 100 / 10_000) + 2 * 10
+            compute: 250 + 20,
+            // 3 * 1000 + ceil(1025 * 50 / 1024)
+            read: 3000 + 51,
+            // 1 * 3000 + ceil(100 * 200 / 1024)
+            write: 3000 + 20,
+            // ceil(1 * 300 / 1024)
+            events: 1,
+        }
+    );
+    assert_eq!(fee.non_refundable(), 270 + 3051 + 3020);
+    assert_eq!(fee.refundable(), 1);
+    assert_eq!(fee.total(), 270 + 3051 + 3020 + 1);
+}
+
+#[test]
+fn fee_saturates() {
+    let resources = InvocationResources {
+        cpu_insns: u64::MAX,
+        ..Default::default()
+    };
+    let schedule = FeeSchedule {
+        fee_per_instruction_increment: i64::MAX,
+        fee_per_contract_event_1kb: i64::MAX,
+        ..Default::default()
+    };
+    let fee = compute_fee(&resources, &schedule);
+    assert_eq!(fee.compute, i64::MAX);
+    assert_eq!(fee.events, 0);
+    assert_eq!(fee.total(), i64::MAX);
+}
+
+#[test]
+fn resources_from_invocation() -> Result<(), HostError> {
+    let host = Host::default();
+    let id = host.bytes_new_from_slice(&[0; 32])?;
+    host.register_test_contract(id, Rc::new(ContractWithSingleEvent {}))?;
+    let args = host.test_vec_obj::<i32>(&[1, 2])?;
+    host.call(id, Symbol::from_str("add").into(), args.into())?;
+
+    let (storage, budget, events) = host.try_finish().map_err(|(_, e)| e)?;
+    let resources = InvocationResources::from_invocation(&budget, &storage, &events)?;
+
+    let event_len = events
+        .0
+        .iter()
+        .filter_map(|e| match &e.event {
+            Event::Contract(ce) if e.in_successful_contract_call => {
+                let mut buf = Vec::new();
+                ce.write_xdr(&mut buf).unwrap();
+                Some(buf.len() as u64)
+            }
+            _ => None,
+        })
+        .sum::<u64>();
+    assert_ne!(event_len, 0);
+    assert_eq!(resources.contract_events_bytes, event_len);
+    assert_eq!(resources.cpu_insns, budget.get_cpu_insns_count());
+    assert_eq!(resources.mem_bytes, budget.get_mem_bytes_count());
+    assert_eq!(resources.read_entries, 0);
+    assert_eq!(resources.write_entries, 0);
+
+    let fee = compute_fee(&resources, &test_schedule());
+    assert_eq!(fee.read, 0);
+    assert_eq!(fee.write, 0);
+    assert!(fee.events > 0);
+    Ok(())
+}
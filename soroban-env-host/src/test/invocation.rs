@@ -4,7 +4,7 @@ use soroban_env_common::{
 };
 
 use crate::{
-    events::{DebugArg, HostEvent},
+    events::{DebugArg, Event},
     vm::Vm,
     xdr::{Hash, ScHostObjErrorCode, ScStatusType, ScVal, ScVec},
     Env, Host, HostError, Status, Symbol, Tag,
@@ -66,9 +66,9 @@ fn invoke_cross_contract_with_err() -> Result<(), HostError> {
 
     let events = host.get_events()?;
     assert_eq!(events.0.len(), 4);
-    let last_event = events.0.last();
+    let last_event = events.0.last().map(|e| &e.event);
     match last_event {
-        Some(HostEvent::Debug(de)) => {
+        Some(Event::Debug(de)) => {
             assert_eq!(
                 de.msg,
                 Some("contract call invocation resulted in error {}".into())
@@ -98,9 +98,9 @@ fn invoke_cross_contract_with_err() -> Result<(), HostError> {
 
     let events = host.get_events()?;
     assert_eq!(events.0.len(), 8);
-    let last_event = events.0.last();
+    let last_event = events.0.last().map(|e| &e.event);
     match last_event {
-        Some(HostEvent::Debug(de)) => {
+        Some(Event::Debug(de)) => {
             assert_eq!(
                 de.msg,
                 Some("contract call invocation resulted in error {}".into())
@@ -160,9 +160,9 @@ fn invoke_cross_contract_indirect_err() -> Result<(), HostError> {
 
     let events = host.get_events()?;
     assert_eq!(events.0.len(), 9);
-    let last_event = events.0.last();
+    let last_event = events.0.last().map(|e| &e.event);
     match last_event {
-        Some(HostEvent::Debug(de)) => {
+        Some(Event::Debug(de)) => {
             assert_eq!(
                 de.msg,
                 Some("contract call invocation resulted in error {}".into())
@@ -192,9 +192,9 @@ fn invoke_cross_contract_indirect_err() -> Result<(), HostError> {
 
     let events = host.get_events()?;
     assert_eq!(events.0.len(), 18);
-    let last_event = events.0.last();
+    let last_event = events.0.last().map(|e| &e.event);
     match last_event {
-        Some(HostEvent::Debug(de)) => {
+        Some(Event::Debug(de)) => {
             assert_eq!(
                 de.msg,
                 Some("contract call invocation resulted in error {}".into())
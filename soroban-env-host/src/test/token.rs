@@ -1,11 +1,16 @@
 use std::convert::TryInto;
 
 use crate::{
-    auth::RecordedAuthPayload,
-    budget::AsBudget,
+    auth::{
+        nonce_signature_expiration_ledger,
+        testutils::{sign_payload_for_account, sign_recorded_payloads, AuthSigner},
+        unordered_nonce, RecordedAuthPayload,
+    },
+    budget::{AsBudget, CostType},
     host::{Frame, TestContractFrame},
     host_vec,
     native_contract::{
+        account_contract::check_account_authentication,
         base_types::Address,
         contract_error::ContractError,
         testutils::{
@@ -15,18 +20,20 @@ use crate::{
         },
         token::test_token::TestToken,
     },
-    test::util::generate_bytes_array,
+    test::util::{generate_bytes_array, webauthn_client_data, webauthn_signature},
     Host, HostError, LedgerInfo,
 };
 use ed25519_dalek::Keypair;
+use sha2::{Digest, Sha256};
 use soroban_env_common::{
     xdr::{self, AccountFlags, ScAddress, ScVal, ScVec, Uint256},
     xdr::{
         AccountEntry, AccountEntryExt, AccountEntryExtensionV1, AccountEntryExtensionV1Ext,
         AccountEntryExtensionV2, AccountEntryExtensionV2Ext, AccountId, AlphaNum12, AlphaNum4,
         Asset, AssetCode12, AssetCode4, Hash, HostFunctionType, LedgerEntryData, LedgerKey,
-        Liabilities, PublicKey, ScStatusType, SequenceNumber, SignerKey, Thresholds,
-        TrustLineEntry, TrustLineEntryExt, TrustLineEntryV1, TrustLineEntryV1Ext, TrustLineFlags,
+        Liabilities, PublicKey, ScHostAuthErrorCode, ScStatusType, SequenceNumber, Signer,
+        SignerKey, ThresholdIndexes, Thresholds, TrustLineEntry, TrustLineEntryExt,
+        TrustLineEntryV1, TrustLineEntryV1Ext, TrustLineFlags,
     },
     EnvBase, RawVal,
 };
@@ -1449,6 +1456,232 @@ fn test_auth_rejected_for_incorrect_nonce() {
         .unwrap();
 }
 
+#[test]
+fn test_unordered_nonces() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+    let user = TestSigner::account(&test.user_key);
+    test.create_default_account(&user);
+    test.create_default_trustline(&user);
+
+    let args = host_vec![
+        &test.host,
+        admin.address(&test.host),
+        user.address(&test.host),
+        100_i128
+    ];
+    let mint = |nonce: u64| {
+        authorize_single_invocation_with_nonce(
+            &test.host,
+            &admin,
+            &token.id,
+            "mint",
+            args.clone(),
+            Some(nonce),
+        );
+        test.host.call(
+            token.id.clone().into(),
+            Symbol::from_str("mint").into(),
+            args.clone().into(),
+        )
+    };
+
+    // Unordered nonces can be used in any order.
+    mint(unordered_nonce(200, 5)).unwrap();
+    mint(unordered_nonce(150, 3)).unwrap();
+    // They don't affect the sequential nonce.
+    mint(0).unwrap();
+
+    // But they can't be replayed.
+    assert!(mint(unordered_nonce(200, 5)).is_err());
+
+    // Once the signature has expired, the nonce can't be used either.
+    test.host
+        .with_mut_ledger_info(|li| li.sequence_number = 151)
+        .unwrap();
+    assert!(mint(unordered_nonce(150, 4)).is_err());
+    // The nonces are told apart by their expiration ledger as well.
+    mint(unordered_nonce(300, 3)).unwrap();
+}
+
+#[test]
+fn test_auth_rejected_for_expired_signature() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+    let user = TestSigner::account(&test.user_key);
+    test.create_default_account(&user);
+    test.create_default_trustline(&user);
+
+    let args = host_vec![
+        &test.host,
+        admin.address(&test.host),
+        user.address(&test.host),
+        100_i128
+    ];
+
+    // The current ledger (123) is past the expiration ledger.
+    authorize_single_invocation_with_nonce(
+        &test.host,
+        &admin,
+        &token.id,
+        "mint",
+        args.clone(),
+        Some(unordered_nonce(122, 0)),
+    );
+    assert!(HostError::result_matches_err_status(
+        test.host.call(
+            token.id.clone().into(),
+            Symbol::from_str("mint").into(),
+            args.clone().into(),
+        ),
+        ScHostAuthErrorCode::NotAuthorized
+    ));
+
+    // The signature is still valid at the expiration ledger itself.
+    authorize_single_invocation_with_nonce(
+        &test.host,
+        &admin,
+        &token.id,
+        "mint",
+        args.clone(),
+        Some(unordered_nonce(123, 0)),
+    );
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint").into(),
+            args.clone().into(),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_auth_simulation_with_unsigned_entries() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+    let user = TestSigner::account(&test.user_key);
+    test.create_default_account(&user);
+    test.create_default_trustline(&user);
+
+    let args = host_vec![
+        &test.host,
+        admin.address(&test.host),
+        user.address(&test.host),
+        100_i128
+    ];
+    let admin_address = admin.address(&test.host).to_sc_address().unwrap();
+    let unsigned_entry = xdr::ContractAuth {
+        address_with_nonce: Some(xdr::AddressWithNonce {
+            address: admin_address.clone(),
+            nonce: test
+                .host
+                .read_nonce(&Hash(token.id.to_array().unwrap()), &admin_address)
+                .unwrap(),
+        }),
+        root_invocation: xdr::AuthorizedInvocation {
+            contract_id: Hash(token.id.to_array().unwrap()),
+            function_name: "mint".try_into().unwrap(),
+            args: test.host.call_args_to_scvec(args.clone().into()).unwrap(),
+            sub_invocations: Default::default(),
+        },
+        signature_args: Default::default(),
+    };
+
+    // The entry without signatures is rejected in the regular enforcing mode.
+    test.host
+        .set_authorization_entries(vec![unsigned_entry.clone()])
+        .unwrap();
+    assert!(test
+        .host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint").into(),
+            args.clone().into(),
+        )
+        .is_err());
+
+    // In the simulation mode the signature verification is charged for, but
+    // not performed.
+    let budget = test.host.budget_cloned();
+    let verify_input_before = budget.get_input(CostType::VerifyEd25519Sig);
+    test.host
+        .set_authorization_entries_for_simulation(vec![unsigned_entry])
+        .unwrap();
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint").into(),
+            args.clone().into(),
+        )
+        .unwrap();
+    let simulated_verify_input = budget.get_input(CostType::VerifyEd25519Sig) - verify_input_before;
+    assert_eq!(simulated_verify_input, 32);
+
+    // The same amount is charged when the signature is actually verified.
+    authorize_single_invocation(&test.host, &admin, &token.id, "mint", args.clone());
+    let verify_input_before = budget.get_input(CostType::VerifyEd25519Sig);
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint").into(),
+            args.clone().into(),
+        )
+        .unwrap();
+    assert_eq!(
+        budget.get_input(CostType::VerifyEd25519Sig) - verify_input_before,
+        simulated_verify_input
+    );
+}
+
+#[test]
+fn test_auth_simulation_fails_for_missing_account_contract() {
+    let test = TokenTest::setup();
+    let token = test.default_token();
+    let missing_contract = contract_id_to_address(&test.host, generate_bytes_array());
+    let user = TestSigner::account(&test.user_key);
+
+    let args = host_vec![
+        &test.host,
+        missing_contract.clone(),
+        user.address(&test.host),
+        100_i128
+    ];
+    let contract_address = missing_contract.to_sc_address().unwrap();
+    let unsigned_entry = xdr::ContractAuth {
+        address_with_nonce: Some(xdr::AddressWithNonce {
+            address: contract_address.clone(),
+            nonce: test
+                .host
+                .read_nonce(&Hash(token.id.to_array().unwrap()), &contract_address)
+                .unwrap(),
+        }),
+        root_invocation: xdr::AuthorizedInvocation {
+            contract_id: Hash(token.id.to_array().unwrap()),
+            function_name: "incr_allow".try_into().unwrap(),
+            args: test.host.call_args_to_scvec(args.clone().into()).unwrap(),
+            sub_invocations: Default::default(),
+        },
+        signature_args: Default::default(),
+    };
+
+    // Only the failures of `check_auth` itself are ignored in the simulation
+    // mode; there is no account contract to run it here.
+    test.host
+        .set_authorization_entries_for_simulation(vec![unsigned_entry])
+        .unwrap();
+    assert!(HostError::result_matches_err_status(
+        test.host.call(
+            token.id.clone().into(),
+            Symbol::from_str("incr_allow").into(),
+            args.into(),
+        ),
+        xdr::ScHostStorageErrorCode::AccessToUnknownEntry
+    ));
+}
+
 #[test]
 fn test_auth_rejected_for_incorrect_payload() {
     let test = TokenTest::setup();
@@ -1777,6 +2010,181 @@ fn test_classic_account_multisig_auth() {
     );
 }
 
+#[test]
+fn test_classic_account_secp256r1_signer_auth() {
+    let test = TokenTest::setup();
+    let account_id = keypair_to_account_id(&test.user_key);
+    test.create_account(
+        &account_id,
+        vec![(&test.user_key_4, 59)],
+        100_000_000,
+        1,
+        [40, 10, 100, 150],
+        None,
+        None,
+        0,
+    );
+    // Register a secp256r1 key as a signer via the hash of its compressed
+    // form.
+    let secp256r1_key = p256::ecdsa::SigningKey::from_bytes(&[7; 32]).unwrap();
+    let secp256r1_key_hash: [u8; 32] = Sha256::digest(
+        secp256r1_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes(),
+    )
+    .into();
+    let mut account = test.host.load_account(account_id.clone()).unwrap();
+    let mut signers = account.signers.to_vec();
+    signers.push(Signer {
+        key: SignerKey::PreAuthTx(Uint256(secp256r1_key_hash)),
+        weight: 60,
+    });
+    account.signers = signers.try_into().unwrap();
+    test.host
+        .add_ledger_entry(
+            test.host.to_account_key(account_id.clone()),
+            Host::ledger_entry_from_data(LedgerEntryData::Account(account)),
+        )
+        .unwrap();
+
+    let payload = [9u8; 32];
+    let mut auth_data = [0u8; 37];
+    auth_data[32] = 0x01;
+    let webauthn_sig = |key: &p256::ecdsa::SigningKey| -> RawVal {
+        webauthn_signature(&test.host, key, &auth_data, &webauthn_client_data(&payload))
+            .unwrap()
+            .try_into_val(&test.host)
+            .unwrap()
+    };
+    let ed25519_sig = |key: &Keypair| -> RawVal {
+        sign_payload_for_account(&test.host, key, &payload)
+            .unwrap()
+            .try_into_val(&test.host)
+            .unwrap()
+    };
+    // Signatures are ordered by the ed25519 key or the secp256r1 key hash.
+    let ordered = |sigs: Vec<([u8; 32], RawVal)>| -> Vec<RawVal> {
+        let mut sigs = sigs;
+        sigs.sort_by_key(|(id, _)| *id);
+        let mut vec = HostVec::new(&test.host).unwrap();
+        for (_, sig) in sigs {
+            vec.push_raw(sig).unwrap();
+        }
+        vec![vec.into()]
+    };
+    let check = |signature_args: Vec<RawVal>| {
+        check_account_authentication(
+            &test.host,
+            &account_id,
+            &payload,
+            &signature_args,
+            ThresholdIndexes::Med,
+        )
+    };
+
+    // Success: 40 + 60 = 100
+    check(ordered(vec![
+        (test.user_key.public.to_bytes(), ed25519_sig(&test.user_key)),
+        (secp256r1_key_hash, webauthn_sig(&secp256r1_key)),
+    ]))
+    .unwrap();
+
+    // Success: 59 + 60 > 100
+    check(ordered(vec![
+        (
+            test.user_key_4.public.to_bytes(),
+            ed25519_sig(&test.user_key_4),
+        ),
+        (secp256r1_key_hash, webauthn_sig(&secp256r1_key)),
+    ]))
+    .unwrap();
+
+    // Failure: 60 < 100
+    assert!(check(ordered(vec![(
+        secp256r1_key_hash,
+        webauthn_sig(&secp256r1_key)
+    )]))
+    .is_err());
+
+    // Failure: the secp256r1 key doesn't belong to the account
+    let other_key = p256::ecdsa::SigningKey::from_bytes(&[8; 32]).unwrap();
+    let other_key_hash: [u8; 32] =
+        Sha256::digest(other_key.verifying_key().to_encoded_point(true).as_bytes()).into();
+    assert!(check(ordered(vec![
+        (test.user_key.public.to_bytes(), ed25519_sig(&test.user_key)),
+        (
+            test.user_key_4.public.to_bytes(),
+            ed25519_sig(&test.user_key_4)
+        ),
+        (other_key_hash, webauthn_sig(&other_key)),
+    ]))
+    .is_err());
+
+    // Failure: duplicate signatures
+    assert!(check(ordered(vec![
+        (secp256r1_key_hash, webauthn_sig(&secp256r1_key)),
+        (secp256r1_key_hash, webauthn_sig(&secp256r1_key)),
+    ]))
+    .is_err());
+
+    // Failure: signatures are not ordered
+    let mut sigs = vec![
+        (test.user_key.public.to_bytes(), ed25519_sig(&test.user_key)),
+        (secp256r1_key_hash, webauthn_sig(&secp256r1_key)),
+    ];
+    sigs.sort_by_key(|(id, _)| *id);
+    let mut reversed = HostVec::new(&test.host).unwrap();
+    for (_, sig) in sigs.into_iter().rev() {
+        reversed.push_raw(sig).unwrap();
+    }
+    assert!(check(vec![reversed.into()]).is_err());
+}
+
+#[test]
+fn test_set_admin_requires_high_threshold() {
+    let test = TokenTest::setup();
+
+    let admin_id = keypair_to_account_id(&test.user_key);
+    test.create_account(
+        &admin_id,
+        vec![(&test.user_key_2, 40)],
+        100_000_000,
+        1,
+        // Low and medium thresholds are reachable with the master key alone,
+        // but the high one also requires the second signer.
+        [10, 10, 10, 50],
+        None,
+        None,
+        0,
+    );
+    let admin_address = TestSigner::account(&test.user_key).address(&test.host);
+    let token = test.default_token_with_admin_id(&admin_address);
+    let new_admin = TestSigner::account(&test.user_key_3).address(&test.host);
+
+    // Failure: 10 < 50
+    assert_eq!(
+        to_contract_err(
+            token
+                .set_admin(
+                    &TestSigner::account_with_multisig(&admin_id, vec![&test.user_key]),
+                    new_admin.clone(),
+                )
+                .err()
+                .unwrap()
+        ),
+        ContractError::AuthenticationError
+    );
+
+    // Success: 10 + 40 = 50
+    token
+        .set_admin(
+            &TestSigner::account_with_multisig(&admin_id, vec![&test.user_key, &test.user_key_2]),
+            new_admin,
+        )
+        .unwrap();
+}
+
 #[test]
 fn test_negative_amounts_are_not_allowed() {
     let test = TokenTest::setup();
@@ -2383,9 +2791,9 @@ fn simple_account_sign_fn<'a>(
     host: &'a Host,
     kp: &'a Keypair,
 ) -> Box<dyn Fn(&[u8]) -> HostVec + 'a> {
-    use crate::native_contract::testutils::sign_payload_for_ed25519;
+    use crate::auth::testutils::sign_payload_for_ed25519;
     Box::new(|payload: &[u8]| -> HostVec {
-        let signature = sign_payload_for_ed25519(host, kp, payload);
+        let signature = sign_payload_for_ed25519(host, kp, payload).unwrap();
         host_vec![host, signature]
     })
 }
@@ -2558,3 +2966,295 @@ fn test_recording_auth_for_token() {
         )]
     );
 }
+
+#[test]
+fn test_recording_auth_with_unordered_nonces() {
+    let test = TokenTest::setup();
+
+    let token = test.default_token();
+
+    let admin = TestSigner::account(&test.issuer_key);
+    let user = TestSigner::account(&test.user_key);
+    test.create_default_account(&user);
+    test.create_default_trustline(&user);
+    test.host
+        .switch_to_recording_auth_with_unordered_nonces([7; 32], 100);
+
+    let args = host_vec![
+        &test.host,
+        admin.address(&test.host),
+        user.address(&test.host),
+        100_i128
+    ];
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint"),
+            args.clone().into(),
+        )
+        .unwrap();
+    let recorded_payloads = test.host.get_recorded_auth_payloads().unwrap();
+    assert_eq!(recorded_payloads.len(), 1);
+    let nonce = recorded_payloads[0].nonce.unwrap();
+    assert_eq!(nonce_signature_expiration_ledger(nonce), Some(223));
+
+    // The subsequent invocations record new nonces.
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint"),
+            args.clone().into(),
+        )
+        .unwrap();
+    assert_ne!(
+        test.host.get_recorded_auth_payloads().unwrap()[0].nonce,
+        Some(nonce)
+    );
+
+    // Recording is deterministic for the same seed.
+    test.host
+        .switch_to_recording_auth_with_unordered_nonces([7; 32], 100);
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint"),
+            args.into(),
+        )
+        .unwrap();
+    assert_eq!(
+        test.host.get_recorded_auth_payloads().unwrap()[0].nonce,
+        Some(nonce)
+    );
+}
+
+#[test]
+fn test_sign_recorded_auth_payloads() {
+    let test = TokenTest::setup();
+
+    let token = test.default_token();
+
+    let admin = TestSigner::account(&test.issuer_key);
+    let user = TestSigner::account(&test.user_key);
+    test.create_default_account(&user);
+    test.create_default_trustline(&user);
+    test.host
+        .switch_to_recording_auth_with_unordered_nonces([7; 32], 100);
+
+    let args = host_vec![
+        &test.host,
+        admin.address(&test.host),
+        user.address(&test.host),
+        100_i128
+    ];
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint"),
+            args.clone().into(),
+        )
+        .unwrap();
+    let recorded_payloads = test.host.get_recorded_auth_payloads().unwrap();
+
+    // Signing fails without a signer for the recorded address.
+    assert!(sign_recorded_payloads(
+        &test.host,
+        &recorded_payloads,
+        &[AuthSigner::ed25519(&test.user_key)]
+    )
+    .is_err());
+
+    let auth_entries = sign_recorded_payloads(
+        &test.host,
+        &recorded_payloads,
+        &[
+            AuthSigner::ed25519(&test.user_key),
+            AuthSigner::ed25519(&test.issuer_key),
+        ],
+    )
+    .unwrap();
+    test.host.set_authorization_entries(auth_entries).unwrap();
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint"),
+            args.into(),
+        )
+        .unwrap();
+    // Both the recorded and the signed invocations have minted the tokens.
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 200);
+}
+
+#[test]
+fn test_sign_recorded_auth_payloads_with_multisig() {
+    let test = TokenTest::setup();
+
+    let account_id = keypair_to_account_id(&test.user_key);
+    test.create_account(
+        &account_id,
+        vec![(&test.user_key_3, 60), (&test.user_key_4, 59)],
+        100_000_000,
+        1,
+        // NB: first threshold is in fact weight of account_id signature.
+        [40, 10, 100, 150],
+        None,
+        None,
+        0,
+    );
+    let token = TestToken::new_from_asset(&test.host, Asset::Native);
+    let user = TestSigner::account_with_multisig(&account_id, vec![]);
+    test.host
+        .switch_to_recording_auth_with_unordered_nonces([7; 32], 100);
+
+    let args = host_vec![
+        &test.host,
+        user.address(&test.host),
+        user.address(&test.host),
+        100_i128
+    ];
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("xfer"),
+            args.clone().into(),
+        )
+        .unwrap();
+    let recorded_payloads = test.host.get_recorded_auth_payloads().unwrap();
+
+    // The signers don't reach the medium threshold: 59 < 100.
+    let auth_entries = sign_recorded_payloads(
+        &test.host,
+        &recorded_payloads,
+        &[AuthSigner::account_multisig(
+            account_id.clone(),
+            vec![&test.user_key_4],
+        )],
+    )
+    .unwrap();
+    test.host.set_authorization_entries(auth_entries).unwrap();
+    assert!(test
+        .host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("xfer"),
+            args.clone().into(),
+        )
+        .is_err());
+
+    // 60 + 59 >= 100; the signers don't need to be passed in the key order.
+    let auth_entries = sign_recorded_payloads(
+        &test.host,
+        &recorded_payloads,
+        &[AuthSigner::account_multisig(
+            account_id.clone(),
+            vec![&test.user_key_4, &test.user_key_3],
+        )],
+    )
+    .unwrap();
+    test.host.set_authorization_entries(auth_entries).unwrap();
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("xfer"),
+            args.into(),
+        )
+        .unwrap();
+}
+
+#[cfg(feature = "vm")]
+#[test]
+fn test_sign_recorded_auth_payloads_for_custom_account() {
+    use crate::auth::testutils::sign_payload_for_ed25519;
+    use soroban_test_wasms::SIMPLE_ACCOUNT_CONTRACT;
+
+    let test = TokenTest::setup();
+    let admin_kp = generate_keypair();
+    let account_contract_id_obj = test
+        .host
+        .register_test_contract_wasm(SIMPLE_ACCOUNT_CONTRACT)
+        .unwrap();
+    let account_contract_id = test
+        .host
+        .hash_from_obj_input("account_contract_id", account_contract_id_obj)
+        .unwrap();
+    let admin_public_key = BytesN::<32>::try_from_val(
+        &test.host,
+        &test
+            .host
+            .bytes_new_from_slice(admin_kp.public.as_bytes().as_slice())
+            .unwrap(),
+    )
+    .unwrap();
+    test.host
+        .call(
+            account_contract_id_obj,
+            Symbol::from_str("init"),
+            host_vec![&test.host, admin_public_key].into(),
+        )
+        .unwrap();
+
+    let admin_address = contract_id_to_address(&test.host, account_contract_id.0);
+    let token = test.default_token_with_admin_id(&admin_address);
+    let user = TestSigner::account(&test.user_key);
+    test.create_default_account(&user);
+    test.create_default_trustline(&user);
+    test.host
+        .switch_to_recording_auth_with_unordered_nonces([7; 32], 100);
+
+    let args = host_vec![
+        &test.host,
+        admin_address,
+        user.address(&test.host),
+        100_i128
+    ];
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint"),
+            args.clone().into(),
+        )
+        .unwrap();
+    let recorded_payloads = test.host.get_recorded_auth_payloads().unwrap();
+
+    let sign_with = |kp: &Keypair| {
+        sign_recorded_payloads(
+            &test.host,
+            &recorded_payloads,
+            &[AuthSigner::contract(
+                account_contract_id.clone(),
+                |host: &Host, payload: &[u8; 32]| {
+                    let mut signature_args = HostVec::new(host)?;
+                    signature_args.push(&sign_payload_for_ed25519(host, kp, payload)?)?;
+                    host.call_args_to_scvec(signature_args.into())
+                },
+            )],
+        )
+        .unwrap()
+    };
+
+    // The account contract rejects the signatures of another key.
+    let other_kp = generate_keypair();
+    test.host
+        .set_authorization_entries(sign_with(&other_kp))
+        .unwrap();
+    assert!(test
+        .host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint"),
+            args.clone().into(),
+        )
+        .is_err());
+
+    test.host
+        .set_authorization_entries(sign_with(&admin_kp))
+        .unwrap();
+    test.host
+        .call(
+            token.id.clone().into(),
+            Symbol::from_str("mint"),
+            args.into(),
+        )
+        .unwrap();
+    // Both the recorded and the signed invocations have minted the tokens.
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 200);
+}
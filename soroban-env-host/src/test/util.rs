@@ -12,9 +12,15 @@ use soroban_env_common::{
 
 use crate::{
     budget::{Budget, CostType},
+    native_contract::{
+        account_contract::{base64_url_encode, AccountWebAuthnSignature},
+        base_types::{Bytes, BytesN},
+    },
     storage::{test_storage::MockSnapshotSource, Storage},
-    xdr, Host, HostError,
+    xdr, EnvBase, Host, HostError, TryFromVal,
 };
+use p256::ecdsa::signature::Signer;
+use sha2::{Digest, Sha256};
 
 // Test utilities for the host, used in various tests in sub-modules.
 pub(crate) trait AsScVal {
@@ -39,6 +45,35 @@ pub(crate) fn generate_account_id() -> AccountId {
     )))
 }
 
+// Client data JSON of a WebAuthn assertion of `payload`.
+pub(crate) fn webauthn_client_data(payload: &[u8]) -> String {
+    format!(
+        r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://example.com","crossOrigin":false}}"#,
+        String::from_utf8(base64_url_encode(payload)).unwrap()
+    )
+}
+
+// Signs `auth_data` and `client` the way a WebAuthn authenticator does.
+pub(crate) fn webauthn_signature(
+    host: &Host,
+    key: &p256::ecdsa::SigningKey,
+    auth_data: &[u8],
+    client: &str,
+) -> Result<AccountWebAuthnSignature, HostError> {
+    let mut message = auth_data.to_vec();
+    message.extend_from_slice(&Sha256::digest(client.as_bytes()));
+    let sig: p256::ecdsa::Signature = key.sign(&message);
+    Ok(AccountWebAuthnSignature {
+        public_key: BytesN::<33>::try_from_val(
+            host,
+            &host.bytes_new_from_slice(key.verifying_key().to_encoded_point(true).as_bytes())?,
+        )?,
+        auth_data: Bytes::try_from_val(host, &host.bytes_new_from_slice(auth_data)?)?,
+        client: Bytes::try_from_val(host, &host.bytes_new_from_slice(client.as_bytes())?)?,
+        signature: BytesN::<64>::try_from_val(host, &host.bytes_new_from_slice(sig.as_ref())?)?,
+    })
+}
+
 pub(crate) fn generate_bytes_array() -> [u8; 32] {
     let mut bytes: [u8; 32] = Default::default();
     thread_rng().fill_bytes(&mut bytes);
@@ -66,6 +66,10 @@ macro_rules! generate_dispatch_functions {
                     // This does not account for the actual work being done in those functions,
                     // which are accounted for individually at the operation level.
                     let host = caller.host_data().clone();
+                    // Attributes all charges made during this call (including
+                    // the flat invocation charge) to this host function when
+                    // budget profiling is enabled.
+                    let _profile_guard = host.budget_ref().enter_host_fn(stringify!($fn_id));
                     host.charge_budget(CostType::InvokeHostFunction, 1)?;
                     let mut vmcaller = VmCaller(Some(caller));
                     // The odd / seemingly-redundant use of `wasmi::Value` here
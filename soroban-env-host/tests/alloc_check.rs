@@ -0,0 +1,31 @@
+#![cfg(feature = "testutils")]
+
+use soroban_env_host::{budget::TrackingAllocationCounter, Env, EnvBase, Host, HostError};
+use std::{alloc::System, rc::Rc};
+use tracking_allocator::Allocator;
+
+#[global_allocator]
+static GLOBAL: Allocator<System> = Allocator::system();
+
+#[test]
+fn allocation_check_through_tracking_allocator() -> Result<(), HostError> {
+    let counter =
+        TrackingAllocationCounter::install().expect("no other global tracker should be set yet");
+    let host = Host::default();
+    host.with_budget(|budget| budget.reset_unlimited());
+    let bytes = host.bytes_new_from_slice(&[0; 10_000])?;
+
+    let (res, report) = host.with_allocation_check(Rc::new(counter), |host| {
+        host.bytes_append(bytes, bytes)?;
+        Ok::<(), HostError>(())
+    });
+    res?;
+
+    // The copies of the bytes are measured and attributed to the cost types
+    // charged before making them.
+    assert!(!report.0.is_empty());
+    assert!(report.0.iter().any(|d| d.charged_bytes > 0));
+    let measured: u64 = report.0.iter().map(|d| d.measured_bytes).sum();
+    assert!(measured >= 10_000);
+    Ok(())
+}
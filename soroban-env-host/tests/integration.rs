@@ -1,5 +1,5 @@
 use soroban_env_host::{
-    events::HostEvent, xdr::ScObjectType, Compare, Env, EnvBase, Host, HostError, Object, RawVal,
+    events::Event, xdr::ScObjectType, Compare, Env, EnvBase, Host, HostError, Object, RawVal,
 };
 
 #[test]
@@ -51,8 +51,8 @@ fn debug_fmt() {
     // Fish out the last debug event and check that it is
     // correct, and formats as expected.
     let events = host.get_events().unwrap();
-    match events.0.last() {
-        Some(HostEvent::Debug(de)) => {
+    match events.0.last().map(|e| &e.event) {
+        Some(Event::Debug(de)) => {
             assert_eq!(
                 format!("{}", de),
                 "can't convert I32(1) to alloc::vec::Vec<u8>"
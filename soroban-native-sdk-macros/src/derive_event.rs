@@ -0,0 +1,114 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, Error, Fields, ItemStruct, Type};
+
+// Converts a struct name (`IncrAllow`) to the event name (`incr_allow`).
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+// Maps the type of an event field to the name of its
+// `EventValueType` variant.
+fn event_value_type(ty: &Type) -> Result<TokenStream2, Error> {
+    let ident = match ty {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    };
+    let variant = match ident.as_deref() {
+        Some("Symbol") => "Symbol",
+        Some("Address") => "Address",
+        Some("i128") => "I128",
+        Some("bool") => "Bool",
+        _ => {
+            return Err(Error::new(
+                ty.span(),
+                "unsupported event field type, expected Symbol, Address, i128 or bool",
+            ))
+        }
+    };
+    let variant = format_ident!("{}", variant);
+    Ok(quote! { crate::events::EventValueType::#variant })
+}
+
+pub fn derive_event(mut input: ItemStruct) -> TokenStream2 {
+    let mut errors = Vec::<Error>::new();
+    let ident = input.ident.clone();
+    let name = to_snake_case(&ident.to_string());
+
+    let fields = match &mut input.fields {
+        Fields::Named(fields) => fields,
+        _ => {
+            return Error::new(ident.span(), "contract events must have named fields")
+                .to_compile_error()
+        }
+    };
+
+    let mut topics = Vec::new();
+    let mut data = Vec::new();
+    for f in fields.named.iter_mut() {
+        let is_data = f.attrs.iter().any(|a| a.path.is_ident("data"));
+        // `#[data]` is only meaningful to this macro.
+        f.attrs.retain(|a| !a.path.is_ident("data"));
+        let field_ident = f.ident.clone().expect("named field");
+        let field_name = field_ident.to_string();
+        let spec = match event_value_type(&f.ty) {
+            Ok(type_) => quote! {
+                crate::events::EventParamSpec { name: #field_name, type_: #type_ }
+            },
+            Err(e) => {
+                errors.push(e);
+                quote! {}
+            }
+        };
+        if is_data {
+            data.push((field_ident, spec));
+        } else {
+            topics.push((field_ident, spec));
+        }
+    }
+    if data.len() != 1 {
+        errors.push(Error::new(
+            ident.span(),
+            "contract events must have exactly one #[data] field",
+        ));
+    }
+
+    if !errors.is_empty() {
+        let compile_errors = errors.iter().map(Error::to_compile_error);
+        return quote! { #(#compile_errors)* };
+    }
+
+    let (topic_fields, topic_specs): (Vec<_>, Vec<_>) = topics.into_iter().unzip();
+    let (data_field, data_spec) = data.remove(0);
+    quote! {
+        #input
+
+        impl #ident {
+            pub(crate) const SPEC: crate::events::ContractEventSpec = crate::events::ContractEventSpec {
+                name: #name,
+                topics: &[#(#topic_specs,)*],
+                data: #data_spec,
+            };
+
+            pub(crate) fn publish(&self, host: &crate::Host) -> Result<(), crate::HostError> {
+                use soroban_env_common::{Env, TryIntoVal};
+                let mut topics = crate::native_contract::base_types::Vec::new(host)?;
+                topics.push(&{ const k: soroban_env_common::Symbol = soroban_env_common::Symbol::from_str(#name); k })?;
+                #(topics.push(&self.#topic_fields)?;)*
+                host.contract_event(topics.into(), self.#data_field.try_into_val(host)?)?;
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,15 +1,17 @@
+mod derive_event;
 mod derive_fn;
 mod derive_type;
 
 extern crate proc_macro;
 
+use crate::derive_event::derive_event;
 use crate::derive_fn::derive_contract_function_set;
 use crate::derive_type::{derive_type_enum, derive_type_struct};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
     parse_macro_input, spanned::Spanned, DeriveInput, Error, ImplItem, ImplItemMethod, ItemImpl,
-    Visibility,
+    ItemStruct, Visibility,
 };
 
 #[proc_macro_attribute]
@@ -22,6 +24,16 @@ pub fn contracttype(_metadata: TokenStream, input: TokenStream) -> TokenStream {
     .into()
 }
 
+/// Declares a contract event. The event name is the struct name in snake
+/// case, the topics are the event name followed by the fields in order, and
+/// the data is the only field marked with `#[data]`. Generates the `publish`
+/// method that emits the event and the `SPEC` constant that describes it.
+#[proc_macro_attribute]
+pub fn contractevent(_metadata: TokenStream, input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ItemStruct);
+    derive_event(input).into()
+}
+
 #[doc(hidden)]
 #[proc_macro_derive(ContractType)]
 pub fn derive_contract_type(input: TokenStream) -> TokenStream {